@@ -0,0 +1,149 @@
+use crate::models::HttpRequest;
+
+/// reference to response of named request, such as `{{login.response.body.$.token}}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseReference {
+    /// variable name in braces, such as `login.response.body.$.token`
+    pub variable: String,
+    /// name of referenced request, and `-` is replaced by `_` alike `@name` tag
+    pub request_name: String,
+    /// path after `response.`: `status`, `body`, `body.<jsonpath>` or `headers.<Name>`
+    pub path: String,
+}
+
+/// parse variable as response reference, and other variables, such as `host` or `$uuid`, are ignored
+pub fn parse_response_reference(variable: &str) -> Option<ResponseReference> {
+    let (request_name, path) = variable.split_once(".response.")?;
+    if request_name.is_empty() || request_name.starts_with('$') || request_name.contains(|c: char| c.is_whitespace() || c == '.') {
+        return None;
+    }
+    if path == "status" || path == "body" || path.starts_with("body.") || path.starts_with("headers.") {
+        Some(ResponseReference { variable: variable.to_string(), request_name: request_name.replace('-', "_"), path: path.to_string() })
+    } else {
+        None
+    }
+}
+
+/// check referenced requests exist, return capturable response, and have no circular dependencies
+pub fn validate_dependencies(requests: &[HttpRequest]) -> Result<(), String> {
+    for request in requests {
+        for reference in request.response_references() {
+            let dependency = find_request(requests, &reference.request_name)
+                .ok_or_else(|| format!("request `{}` not found for `{{{{{}}}}}` in `{}`", reference.request_name, reference.variable, request.name))?;
+            if let Some(message) = capture_error(dependency) {
+                return Err(format!("request `{}` referenced by `{}` {}", dependency.name, request.name, message));
+            }
+        }
+        dependency_order(requests, request)?;
+    }
+    Ok(())
+}
+
+/// dependencies of request in running order, and shared dependencies are included once
+pub fn dependency_order<'a>(requests: &'a [HttpRequest], request: &HttpRequest) -> Result<Vec<&'a HttpRequest>, String> {
+    let mut ordered: Vec<&HttpRequest> = Vec::new();
+    let mut path: Vec<String> = vec![request.name.clone()];
+    visit_dependencies(requests, request, &mut path, &mut ordered)?;
+    Ok(ordered)
+}
+
+fn visit_dependencies<'a>(requests: &'a [HttpRequest], request: &HttpRequest, path: &mut Vec<String>, ordered: &mut Vec<&'a HttpRequest>) -> Result<(), String> {
+    for name in request.dependencies() {
+        if path.contains(&name) {
+            path.push(name);
+            return Err(format!("circular dependency between requests: {}", path.join(" -> ")));
+        }
+        if ordered.iter().any(|dependency| dependency.name == name) {
+            continue;
+        }
+        let dependency = find_request(requests, &name).ok_or_else(|| format!("request `{}` not found for `{}`", name, request.name))?;
+        path.push(name);
+        visit_dependencies(requests, dependency, path, ordered)?;
+        path.pop();
+        ordered.push(dependency);
+    }
+    Ok(())
+}
+
+fn find_request<'a>(requests: &'a [HttpRequest], name: &str) -> Option<&'a HttpRequest> {
+    requests.iter().find(|request| request.name == name)
+}
+
+/// reason why generated function of request can not be run and captured for dependent requests
+fn capture_error(request: &HttpRequest) -> Option<&'static str> {
+    let subscription = request.graphql_operation().is_some_and(|operation| operation.operation_type == "subscription");
+    let streaming = request.get_tag_value("sse").is_some() || subscription || ["WEBSOCKET", "SUB", "PUB", "GRPC", "JSONRPC"].contains(&request.method.as_str());
+    if streaming {
+        Some("should be http, GraphQL, SHELL or SSH request")
+    } else if request.get_tag_value("response-type").is_some() {
+        Some("should not have `@response-type` tag")
+    } else if request.body_type.is_some() || request.is_operation_name_required() {
+        Some("should not take body struct or operation name")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_http_code;
+
+    #[test]
+    fn test_parse_response_reference() {
+        let reference = parse_response_reference("login-user.response.body.$.token").unwrap();
+        assert_eq!(reference.request_name, "login_user");
+        assert_eq!(reference.path, "body.$.token");
+        assert!(parse_response_reference("login.response.headers.Location").is_some());
+        assert!(parse_response_reference("host").is_none());
+        assert!(parse_response_reference("login.response.cookies.id").is_none());
+    }
+
+    #[test]
+    fn test_dependency_order() {
+        let http_code = r#"
+### login
+//@name login
+POST https://{{host}}/login
+
+### profile
+//@name profile
+GET https://{{host}}/users/{{login.response.body.$.id}}
+Authorization: Bearer {{login.response.body.$.token}}
+
+### orders
+//@name orders
+GET https://{{host}}{{profile.response.headers.Location}}/orders
+Authorization: Bearer {{login.response.body.$.token}}
+"#;
+        let requests = parse_http_code(http_code);
+        validate_dependencies(&requests).unwrap();
+        let dependencies = dependency_order(&requests, &requests[2]).unwrap();
+        let names: Vec<&str> = dependencies.iter().map(|request| request.name.as_str()).collect();
+        assert_eq!(names, vec!["login", "profile"]);
+    }
+
+    #[test]
+    fn test_invalid_dependencies() {
+        let http_code = r#"
+### ping
+//@name ping
+GET https://{{host}}/ping?token={{pong.response.body}}
+
+### pong
+//@name pong
+GET https://{{host}}/pong?token={{ping.response.body}}
+"#;
+        let error = validate_dependencies(&parse_http_code(http_code)).unwrap_err();
+        println!("{}", error);
+        assert_eq!(error, "circular dependency between requests: ping -> pong -> ping");
+        let http_code = r#"
+### profile
+//@name profile
+GET https://{{host}}/profile
+Authorization: Bearer {{login.response.body.$.token}}
+"#;
+        let error = validate_dependencies(&parse_http_code(http_code)).unwrap_err();
+        assert_eq!(error, "request `login` not found for `{{login.response.body.$.token}}` in `profile`");
+    }
+}
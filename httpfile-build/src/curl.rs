@@ -0,0 +1,660 @@
+use crate::models::{to_unique_name, HttpRequest};
+use crate::parser::parse_http_code;
+
+pub(crate) const FORM_BOUNDARY: &str = "WebAppBoundary";
+
+/// short options of curl which take value, such as `-X POST` or `-XPOST`
+const SHORT_VALUE_OPTIONS: &str = "XHdFumAebox";
+
+/// convert request to curl command line, and variables in request should be rendered before
+pub fn to_curl_command(request: &HttpRequest) -> String {
+    let mut args: Vec<String> = vec!["curl".to_owned()];
+    let json_post = request.method == "GRAPHQL" || request.method == "JSONRPC";
+    let multipart_boundary = get_multipart_boundary(request);
+    match request.method.as_str() {
+        "GET" => {}
+        "HEAD" => args.push("--head".to_owned()),
+        "GRAPHQL" | "JSONRPC" => args.push("-X POST".to_owned()),
+        method => args.push(format!("-X {}", method)),
+    }
+    if let Some(protocol) = &request.protocol {
+        if protocol.starts_with("HTTP/2") {
+            args.push("--http2".to_owned());
+        }
+    }
+    if request.get_tag_value("insecure").is_some() {
+        args.push("--insecure".to_owned());
+    }
+    if let Some(proxy) = request.get_tag_value("proxy") {
+        args.push(format!("--proxy {}", shell_quote(proxy)));
+    }
+    if let Some(timeout) = request.get_tag_value("timeout").and_then(to_seconds) {
+        args.push(format!("--max-time {}", timeout));
+    }
+    if let Some(timeout) = request.get_tag_value("connection-timeout").and_then(to_seconds) {
+        args.push(format!("--connect-timeout {}", timeout));
+    }
+    for header in &request.headers {
+        // Content-Type with boundary is generated by curl for multipart form
+        if multipart_boundary.is_some() && header.name.eq_ignore_ascii_case("Content-Type") {
+            continue;
+        }
+        // IDE form `Basic user password` and `Digest user password` are passed with `-u`
+        if header.name.eq_ignore_ascii_case("Authorization") {
+            if let Some(user_option) = to_user_option(&header.value) {
+                args.push(user_option);
+                continue;
+            }
+        }
+        args.push(format!("-H {}", shell_quote(&format!("{}: {}", header.name, header.value))));
+    }
+    if json_post && request.get_header_value("Content-Type").is_none() {
+        args.push(format!("-H {}", shell_quote("Content-Type: application/json")));
+    }
+    if let Some(body) = &request.body {
+        if let Some(boundary) = &multipart_boundary {
+            for form_field in to_form_fields(body, boundary) {
+                args.push(format!("-F {}", shell_quote(&form_field)));
+            }
+        } else if let Some(file_path) = body.strip_prefix("< ") {
+            args.push(format!("--data-binary {}", shell_quote(&format!("@{}", file_path.trim()))));
+        } else {
+            args.push(format!("--data-binary {}", shell_quote(body)));
+        }
+    }
+    let url = request.uri.as_ref().unwrap_or(&request.path);
+    args.push(shell_quote(url));
+    args.join(" \\\n  ")
+}
+
+/// `-u user:password` for `Basic user password` or `Basic user:password`, and `--digest -u user:password` for
+/// `Digest user password`. Encoded Basic credentials are kept in header
+fn to_user_option(value: &str) -> Option<String> {
+    if let Some(credentials) = value.strip_prefix("Basic ") {
+        let credentials = credentials.trim();
+        let user_password = match credentials.split_once(' ') {
+            Some((user, password)) => format!("{}:{}", user, password.trim()),
+            None if credentials.contains(':') => credentials.to_string(),
+            None => return None,
+        };
+        Some(format!("-u {}", shell_quote(&user_password)))
+    } else {
+        let credentials = value.strip_prefix("Digest ")?.trim();
+        let (user, password) = credentials.split_once(' ').unwrap_or((credentials, ""));
+        Some(format!("--digest -u {}", shell_quote(&format!("{}:{}", user, password.trim()))))
+    }
+}
+
+fn get_multipart_boundary(request: &HttpRequest) -> Option<String> {
+    let content_type = request.get_header_value("Content-Type")?;
+    if !content_type.starts_with("multipart/form-data") {
+        return None;
+    }
+    content_type
+        .split(';')
+        .filter_map(|part| part.trim().strip_prefix("boundary="))
+        .map(|boundary| boundary.trim_matches('"').to_string())
+        .next()
+}
+
+/// form fields for `-F`, such as `name=value` or `file=@./demo.png;type=image/png`
+fn to_form_fields(body: &str, boundary: &str) -> Vec<String> {
+    let delimiter = format!("--{}", boundary);
+    let mut form_fields = Vec::new();
+    for part in body.split(&delimiter) {
+        let part = part.trim_start_matches(['\r', '\n']);
+        if part.is_empty() || part.starts_with("--") {
+            continue;
+        }
+        let (part_headers, content) = match part.split_once("\n\n") {
+            Some((part_headers, content)) => (part_headers, content.trim_end_matches(['\r', '\n'])),
+            None => (part, ""),
+        };
+        let mut name = String::new();
+        let mut content_type: Option<&str> = None;
+        for header_line in part_headers.lines() {
+            if let Some((header_name, header_value)) = header_line.split_once(':') {
+                if header_name.trim().eq_ignore_ascii_case("Content-Disposition") {
+                    for param in header_value.split(';') {
+                        if let Some(value) = param.trim().strip_prefix("name=") {
+                            name = value.trim_matches('"').to_string();
+                        }
+                    }
+                } else if header_name.trim().eq_ignore_ascii_case("Content-Type") {
+                    content_type = Some(header_value.trim());
+                }
+            }
+        }
+        let mut form_field = if let Some(file_path) = content.strip_prefix("< ") {
+            format!("{}=@{}", name, file_path.trim())
+        } else {
+            format!("{}={}", name, content)
+        };
+        if let Some(content_type) = content_type {
+            form_field.push_str(&format!(";type={}", content_type));
+        }
+        form_fields.push(form_field);
+    }
+    form_fields
+}
+
+/// timeout in seconds from tag value, such as `600`, `500 ms`, `2 m`
+fn to_seconds(value: &str) -> Option<String> {
+    let value = value.trim();
+    let offset = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let number: u64 = value[..offset].parse().ok()?;
+    match value[offset..].trim() {
+        "" | "s" => Some(number.to_string()),
+        "ms" => Some(format!("{}", number as f64 / 1000.0)),
+        "m" => Some((number * 60).to_string()),
+        _ => None,
+    }
+}
+
+/// quote text with single quotes for shell
+pub fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r#"'\''"#))
+}
+
+/// parse curl command line, such as "Copy as cURL" from browsers, into request with generated name
+pub fn parse_curl_command(command: &str) -> Result<HttpRequest, String> {
+    let mut args = split_command_line(command)?;
+    if args.first().map(|arg| arg.as_str()) != Some("curl") {
+        return Err("curl command should start with `curl`".to_owned());
+    }
+    let mut method: Option<String> = None;
+    let mut url: Option<String> = None;
+    let mut headers: Vec<(String, String)> = Vec::new();
+    let mut data: Vec<String> = Vec::new();
+    let mut form_fields: Vec<String> = Vec::new();
+    let mut tags: Vec<String> = Vec::new();
+    let mut protocol: Option<&str> = None;
+    let mut use_get = false;
+    let mut digest = false;
+    let mut i = 1;
+    while i < args.len() {
+        let arg = args[i].clone();
+        if !arg.starts_with('-') {
+            i += 1;
+            url = Some(arg);
+            continue;
+        }
+        // grouped short options, such as `-sSL` or `-kXPOST`, are split into single options
+        if let Some(options) = split_short_options(&arg) {
+            args.splice(i..=i, options);
+            continue;
+        }
+        i += 1;
+        // short option with attached value, such as `-XPOST`
+        let (option, attached_value) = match arg.chars().nth(1) {
+            Some(c) if !arg.starts_with("--") && arg.len() > 2 && SHORT_VALUE_OPTIONS.contains(c) => (&arg[..2], Some(arg[2..].to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let takes_value = matches!(option,
+            "-X" | "--request" | "-H" | "--header" | "-d" | "--data" | "--data-raw" | "--data-binary" | "--data-ascii" | "--data-urlencode"
+            | "-F" | "--form" | "-u" | "--user" | "-A" | "--user-agent" | "-e" | "--referer" | "-b" | "--cookie" | "--url"
+            | "-m" | "--max-time" | "--connect-timeout" | "-o" | "--output" | "-x" | "--proxy"
+        );
+        let value = if !takes_value {
+            String::new()
+        } else if let Some(value) = attached_value {
+            value
+        } else if i < args.len() {
+            i += 1;
+            args[i - 1].clone()
+        } else {
+            return Err(format!("value missing for option {}", option));
+        };
+        match option {
+            "-X" | "--request" => method = Some(value.to_uppercase()),
+            "-H" | "--header" => {
+                if let Some((name, header_value)) = value.split_once(':') {
+                    headers.push((name.trim().to_string(), header_value.trim().to_string()));
+                }
+            }
+            "-d" | "--data" | "--data-ascii" | "--data-binary" => {
+                if let Some(file_path) = value.strip_prefix('@') {
+                    data.push(format!("< {}", file_path));
+                } else {
+                    data.push(value);
+                }
+            }
+            "--data-raw" => data.push(value),
+            "--data-urlencode" => data.push(to_urlencoded_data(&value)),
+            "-F" | "--form" => form_fields.push(value),
+            "-u" | "--user" => {
+                let (user, password) = value.split_once(':').unwrap_or((&value, ""));
+                headers.push(("Authorization".to_owned(), format!("Basic {} {}", user, password).trim_end().to_string()));
+            }
+            "-A" | "--user-agent" => headers.push(("User-Agent".to_owned(), value)),
+            "-e" | "--referer" => headers.push(("Referer".to_owned(), value)),
+            "-b" | "--cookie" => headers.push(("Cookie".to_owned(), value)),
+            "--url" => url = Some(value),
+            "-m" | "--max-time" => tags.push(format!("timeout {}", value)),
+            "--connect-timeout" => tags.push(format!("connection-timeout {}", value)),
+            "--compressed" => headers.push(("Accept-Encoding".to_owned(), "gzip, deflate".to_owned())),
+            "-k" | "--insecure" => tags.push("insecure".to_owned()),
+            "-x" | "--proxy" => tags.push(format!("proxy {}", value)),
+            "-I" | "--head" => method = Some("HEAD".to_owned()),
+            "-G" | "--get" => use_get = true,
+            "--digest" => digest = true,
+            "--http2" => protocol = Some("HTTP/2"),
+            _ => {}
+        }
+    }
+    if digest {
+        for (name, value) in headers.iter_mut() {
+            if let Some(credentials) = value.strip_prefix("Basic ").filter(|_| name == "Authorization") {
+                *value = format!("Digest {}", credentials);
+            }
+        }
+    }
+    let mut url = url.ok_or("url missing in curl command")?;
+    if !url.contains("://") {
+        url = format!("http://{}", url);
+    }
+    let mut body: Option<String> = None;
+    if use_get {
+        if !data.is_empty() {
+            url.push(if url.contains('?') { '&' } else { '?' });
+            url.push_str(&data.join("&"));
+        }
+    } else if !form_fields.is_empty() {
+        headers.retain(|(name, _)| !name.eq_ignore_ascii_case("Content-Type"));
+        headers.push(("Content-Type".to_owned(), format!("multipart/form-data; boundary={}", FORM_BOUNDARY)));
+        body = Some(to_multipart_body(&form_fields));
+    } else if !data.is_empty() {
+        if !headers.iter().any(|(name, _)| name.eq_ignore_ascii_case("Content-Type")) {
+            headers.push(("Content-Type".to_owned(), "application/x-www-form-urlencoded".to_owned()));
+        }
+        body = Some(data.join("&"));
+    }
+    let method = method.unwrap_or_else(|| if body.is_some() { "POST".to_owned() } else { "GET".to_owned() });
+    // build http code and parse it to keep request same as the one from http file
+    let mut code_lines: Vec<String> = vec!["###".to_owned(), format!("//@name {}", generate_name(&method, &url))];
+    for tag in tags {
+        code_lines.push(format!("//@{}", tag));
+    }
+    match protocol {
+        Some(protocol) => code_lines.push(format!("{} {} {}", method, url, protocol)),
+        None => code_lines.push(format!("{} {}", method, url)),
+    }
+    for (name, value) in headers {
+        code_lines.push(format!("{}: {}", name, value));
+    }
+    if let Some(body) = body {
+        code_lines.push("".to_owned());
+        code_lines.push(body);
+    }
+    parse_http_code(&code_lines.join("\n")).pop().ok_or_else(|| "failed to parse curl command".to_owned())
+}
+
+/// split grouped short options, such as `-sSL` into `-s`, `-S` and `-L`, and option taking value ends the group
+fn split_short_options(arg: &str) -> Option<Vec<String>> {
+    let letters = arg.strip_prefix('-').filter(|letters| !letters.starts_with('-'))?;
+    let mut options: Vec<String> = Vec::new();
+    for (offset, c) in letters.char_indices() {
+        if SHORT_VALUE_OPTIONS.contains(c) {
+            options.push(format!("-{}", &letters[offset..]));
+            break;
+        }
+        options.push(format!("-{}", c));
+    }
+    if options.len() > 1 {
+        Some(options)
+    } else {
+        None
+    }
+}
+
+/// data of `--data-urlencode`: `content`, `=content` or `name=content`, and `@file` forms are kept as they are
+fn to_urlencoded_data(value: &str) -> String {
+    if value.contains('@') && !value.contains('=') {
+        return value.to_string();
+    }
+    match value.split_once('=') {
+        Some(("", content)) => url_encode(content),
+        Some((name, content)) => format!("{}={}", name, url_encode(content)),
+        None => url_encode(value),
+    }
+}
+
+/// percent-encode text alike curl, and `{{variable}}` placeholders are kept
+fn url_encode(text: &str) -> String {
+    let mut encoded = String::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        if let Some(end) = rest.strip_prefix("{{").and_then(|_| rest.find("}}")) {
+            encoded.push_str(&rest[..end + 2]);
+            rest = &rest[end + 2..];
+            continue;
+        }
+        let c = rest.chars().next().unwrap();
+        if c.is_ascii_alphanumeric() || "-._~".contains(c) {
+            encoded.push(c);
+        } else {
+            let mut buffer = [0; 4];
+            for byte in c.encode_utf8(&mut buffer).bytes() {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    encoded
+}
+
+/// append request from curl command to http code, and request name is unique in http code
+pub fn import_curl_command(http_code: &str, command: &str) -> Result<String, String> {
+    let mut request = parse_curl_command(command)?;
+    let names: Vec<String> = parse_http_code(http_code).into_iter().map(|request| request.name).collect();
+    let name = to_unique_name(request.get_tag_value("name").unwrap_or_default(), &names);
+    request.name = name.replace('-', "_");
+    for tag in request.tags.iter_mut() {
+        if tag.starts_with("name ") {
+            *tag = format!("name {}", name);
+        }
+    }
+    let mut code = http_code.trim_end().to_string();
+    if !code.is_empty() {
+        code.push_str("\n\n");
+    }
+    code.push_str(&request.to_http_code());
+    code.push('\n');
+    Ok(code)
+}
+
+/// name from method and last path segment, such as `post-users` for `POST https://example.com/users/1`
+fn generate_name(method: &str, url: &str) -> String {
+    let path = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let segment = path
+        .split('/')
+        .skip(1)
+        .filter(|segment| !segment.is_empty() && !segment.contains("{{") && !segment.chars().all(|c| c.is_ascii_digit()))
+        .last()
+        .unwrap_or_default();
+    let segment: String = segment
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let segment = segment.trim_matches('-');
+    if segment.is_empty() {
+        method.to_lowercase()
+    } else {
+        format!("{}-{}", method.to_lowercase(), segment)
+    }
+}
+
+pub(crate) fn to_multipart_body(form_fields: &[String]) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for form_field in form_fields {
+        let (name, value) = form_field.split_once('=').unwrap_or((form_field, ""));
+        let mut params = value.split(';');
+        let value = params.next().unwrap_or_default();
+        let content_type = params.filter_map(|param| param.trim().strip_prefix("type=")).next();
+        lines.push(format!("--{}", FORM_BOUNDARY));
+        if let Some(file_path) = value.strip_prefix('@') {
+            let file_name = file_path.rsplit('/').next().unwrap_or(file_path);
+            lines.push(format!(r#"Content-Disposition: form-data; name="{}"; filename="{}""#, name, file_name));
+            if let Some(content_type) = content_type {
+                lines.push(format!("Content-Type: {}", content_type));
+            }
+            lines.push("".to_owned());
+            lines.push(format!("< {}", file_path));
+        } else {
+            lines.push(format!(r#"Content-Disposition: form-data; name="{}""#, name));
+            if let Some(content_type) = content_type {
+                lines.push(format!("Content-Type: {}", content_type));
+            }
+            lines.push("".to_owned());
+            lines.push(value.to_string());
+        }
+    }
+    lines.push(format!("--{}--", FORM_BOUNDARY));
+    lines.join("\n")
+}
+
+/// split command line into arguments with shell quoting rules: `'...'`, `"..."`, `$'...'` and `\` line continuation
+fn split_command_line(command: &str) -> Result<Vec<String>, String> {
+    let mut args: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some('\r') => {
+                    chars.next_if_eq(&'\n');
+                }
+                Some(escaped) => {
+                    current.push(escaped);
+                    in_arg = true;
+                }
+                None => {}
+            },
+            '\'' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err("unclosed single quote".to_owned()),
+                    }
+                }
+            }
+            '"' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped @ ('"' | '\\' | '$' | '`')) => current.push(escaped),
+                            Some('\n') => {}
+                            Some(other) => {
+                                current.push('\\');
+                                current.push(other);
+                            }
+                            None => return Err("unclosed double quote".to_owned()),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err("unclosed double quote".to_owned()),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => current.push('\n'),
+                            Some('r') => current.push('\r'),
+                            Some('t') => current.push('\t'),
+                            Some(escaped) => current.push(escaped),
+                            None => return Err("unclosed single quote".to_owned()),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err("unclosed single quote".to_owned()),
+                    }
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_http_code;
+
+    #[test]
+    fn test_to_curl_command() {
+        let http_code = r#"
+### post test
+//@name post-test
+//@timeout 2 m
+POST https://httpbin.org/post HTTP/2
+Content-Type: application/json
+
+{"name": "it's me"}
+"#;
+        let requests = parse_http_code(http_code);
+        let curl_command = to_curl_command(&requests[0]);
+        println!("{}", curl_command);
+        assert_eq!(
+            curl_command,
+            r#"curl \
+  -X POST \
+  --http2 \
+  --max-time 120 \
+  -H 'Content-Type: application/json' \
+  --data-binary '{"name": "it'\''s me"}' \
+  'https://httpbin.org/post'"#
+        );
+    }
+
+    #[test]
+    fn test_auth_to_curl_command() {
+        let http_code = r#"
+### basic
+GET https://httpbin.org/basic-auth/admin/secret
+Authorization: Basic admin secret
+
+### digest
+GET https://httpbin.org/digest-auth/auth/admin/it's
+Authorization: Digest admin it's
+
+### encoded
+GET https://httpbin.org/basic-auth/admin/secret
+Authorization: Basic YWRtaW46c2VjcmV0
+"#;
+        let requests = parse_http_code(http_code);
+        let curl_command = to_curl_command(&requests[0]);
+        println!("{}", curl_command);
+        assert_eq!(curl_command, "curl \\\n  -u 'admin:secret' \\\n  'https://httpbin.org/basic-auth/admin/secret'");
+        let curl_command = to_curl_command(&requests[1]);
+        assert!(curl_command.contains(r#"--digest -u 'admin:it'\''s'"#));
+        assert!(!curl_command.contains("Authorization"));
+        let request = parse_curl_command(&curl_command.replace(" \\\n ", "")).unwrap();
+        assert_eq!(request.get_header_value("Authorization"), Some("Digest admin it's"));
+        assert!(to_curl_command(&requests[2]).contains("-H 'Authorization: Basic YWRtaW46c2VjcmV0'"));
+        // `-u` is imported back as IDE form
+        let request = parse_curl_command(&to_curl_command(&requests[0]).replace(" \\\n ", "")).unwrap();
+        assert_eq!(request.get_header_value("Authorization"), Some("Basic admin secret"));
+    }
+
+    #[test]
+    fn test_multipart_to_curl_command() {
+        let http_code = r#"
+### upload
+POST https://httpbin.org/post
+Content-Type: multipart/form-data; boundary=WebAppBoundary
+
+--WebAppBoundary
+Content-Disposition: form-data; name="element-name"
+Content-Type: text/plain
+
+Name
+--WebAppBoundary
+Content-Disposition: form-data; name="data"; filename="data.json"
+Content-Type: application/json
+
+< ./request-form-data.json
+--WebAppBoundary--
+"#;
+        let requests = parse_http_code(http_code);
+        let curl_command = to_curl_command(&requests[0]);
+        println!("{}", curl_command);
+        assert!(curl_command.contains("-F 'element-name=Name;type=text/plain'"));
+        assert!(curl_command.contains("-F 'data=@./request-form-data.json;type=application/json'"));
+        assert!(!curl_command.contains("boundary"));
+    }
+
+    #[test]
+    fn test_parse_curl_command() {
+        let command = r#"curl 'https://httpbin.org/post?id=1' \
+  -H 'accept: application/json' \
+  -H "Content-Type: application/json" \
+  --data-raw $'{"name":"it\'s me"}' \
+  -u admin:secret \
+  --compressed -k -x socks5://127.0.0.1:1080"#;
+        let request = parse_curl_command(command).unwrap();
+        println!("{}", request.to_http_code());
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.name, "post_post");
+        assert_eq!(request.uri.as_deref(), Some("https://httpbin.org/post?id=1"));
+        assert_eq!(request.get_header_value("Authorization"), Some("Basic admin secret"));
+        assert_eq!(request.get_header_value("Content-Type"), Some("application/json"));
+        assert_eq!(request.body.as_deref(), Some(r#"{"name":"it's me"}"#));
+        assert!(request.get_tag_value("insecure").is_some());
+        assert!(to_curl_command(&request).contains("--insecure"));
+        assert_eq!(request.get_tag_value("proxy"), Some("socks5://127.0.0.1:1080"));
+        assert!(to_curl_command(&request).contains("--proxy 'socks5://127.0.0.1:1080'"));
+    }
+
+    #[test]
+    fn test_parse_curl_methods() {
+        let request = parse_curl_command("curl -X PATCH https://httpbin.org/patch -d 'id=1'").unwrap();
+        assert_eq!(request.method, "PATCH");
+        assert_eq!(request.body.as_deref(), Some("id=1"));
+        assert_eq!(parse_curl_command("curl -X OPTIONS https://httpbin.org/anything").unwrap().method, "OPTIONS");
+        let request = parse_curl_command("curl -I https://httpbin.org/get").unwrap();
+        assert_eq!(request.method, "HEAD");
+        assert!(to_curl_command(&request).contains("--head"));
+    }
+
+    #[test]
+    fn test_parse_curl_grouped_options() {
+        let request = parse_curl_command("curl -sSkL https://httpbin.org/get").unwrap();
+        assert!(request.get_tag_value("insecure").is_some());
+        let request = parse_curl_command("curl -kXPUT -H 'X-Id: 1' https://httpbin.org/put").unwrap();
+        assert_eq!(request.method, "PUT");
+        assert!(request.get_tag_value("insecure").is_some());
+        assert_eq!(request.get_header_value("X-Id"), Some("1"));
+        // multi-byte option is ignored without panic
+        assert_eq!(parse_curl_command("curl -é https://httpbin.org/get").unwrap().method, "GET");
+    }
+
+    #[test]
+    fn test_parse_curl_data_urlencode() {
+        let command = "curl https://httpbin.org/post --data-urlencode 'name=linux china' --data-urlencode '=a&b' --data-urlencode 'nick={{nick}}!'";
+        let request = parse_curl_command(command).unwrap();
+        assert_eq!(request.body.as_deref(), Some("name=linux%20china&a%26b&nick={{nick}}%21"));
+        assert_eq!(request.get_header_value("Content-Type"), Some("application/x-www-form-urlencoded"));
+    }
+
+    #[test]
+    fn test_parse_curl_form() {
+        let command = "curl -F name=linux_china -F 'avatar=@./avatar.png;type=image/png' https://httpbin.org/users/42";
+        let request = parse_curl_command(command).unwrap();
+        assert_eq!(request.name, "post_users");
+        assert_eq!(to_curl_command(&request), "curl \\\n  -X POST \\\n  -F 'name=linux_china' \\\n  -F 'avatar=@./avatar.png;type=image/png' \\\n  'https://httpbin.org/users/42'");
+    }
+
+    #[test]
+    fn test_import_curl_command() {
+        let http_code = std::fs::read_to_string("index.http").unwrap();
+        let code = import_curl_command(&http_code, "curl https://httpbin.org/ip").unwrap();
+        let code = import_curl_command(&code, "curl https://httpbin.org/ip").unwrap();
+        let requests = parse_http_code(&code);
+        assert_eq!(requests[requests.len() - 2].name, "get_ip");
+        assert_eq!(requests[requests.len() - 1].name, "get_ip_2");
+        assert!(code.ends_with("###\n//@name get-ip-2\nGET https://httpbin.org/ip\n"));
+    }
+}
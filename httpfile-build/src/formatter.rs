@@ -0,0 +1,110 @@
+use crate::models::HttpRequest;
+use crate::parser::parse_http_code;
+
+/// format http file text into canonical style, and `parse_http_code(format_http_code(x))` keeps the same requests
+pub fn format_http_code(http_code: &str) -> String {
+    let requests = parse_http_code(http_code);
+    let mut code = String::new();
+    // keep shebang of first line
+    if let Some(first_line) = http_code.lines().next() {
+        if first_line.trim().starts_with("#!") {
+            code.push_str(first_line.trim());
+            code.push_str("\n\n");
+        }
+    }
+    code.push_str(&format_requests(&requests));
+    code
+}
+
+pub fn format_requests(requests: &[HttpRequest]) -> String {
+    let mut code = requests
+        .iter()
+        .map(|request| request.to_http_code())
+        .collect::<Vec<String>>()
+        .join("\n\n");
+    code.push('\n');
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_http_code() {
+        let http_code = r#"#!/usr/bin/env httpx
+// get my ip
+#@name my-ip
+GET https://httpbin.org/ip
+user-agent: curl/7.47.0
+###post test
+// post with json body
+//@name post-test
+POST https://{{host}}/post HTTP/1.1
+content-type: application/json
+
+{"name": "{{nick}}", "age": 42}
+
+> {%
+client.global.set("name", response.body.json.name);
+%}
+<> ./post-test.200.json
+### GraphQL demo
+//@name graphql-demo
+GRAPHQL https://httpbin.org/post
+
+query {
+ip
+}
+{
+"id": 1,
+"name": "{{nick}}"
+}
+"#;
+        let formatted_code = format_http_code(http_code);
+        println!("{}", formatted_code);
+        assert_eq!(parse_http_code(&formatted_code), parse_http_code(http_code));
+        assert_eq!(format_http_code(&formatted_code), formatted_code);
+    }
+
+    #[test]
+    fn test_format_comments() {
+        let http_code = r#"###
+//
+GET https://httpbin.org/ip
+Accept: application/json
+// trace id for debugging
+X-Trace-Id: 1
+// end of headers
+
+### second
+GET https://httpbin.org/headers
+"#;
+        let requests = parse_http_code(http_code);
+        assert_eq!(requests[0].comment, None);
+        assert_eq!(requests[0].comments, vec![""]);
+        assert_eq!(requests[0].header_comments, vec![(1, "trace id for debugging".to_string()), (2, "end of headers".to_string())]);
+        let formatted_code = format_http_code(http_code);
+        println!("{}", formatted_code);
+        assert!(formatted_code.lines().all(|line| line == line.trim_end()));
+        assert!(formatted_code.contains("Accept: application/json\n// trace id for debugging\nX-Trace-Id: 1\n// end of headers\n"));
+        assert_eq!(parse_http_code(&formatted_code), requests);
+        assert_eq!(format_http_code(&formatted_code), formatted_code);
+    }
+
+    #[test]
+    fn test_header_name_case_insensitive() {
+        let requests = parse_http_code("GET https://httpbin.org/ip\ncontent-type: application/json\n");
+        assert_eq!(requests[0].get_header_value("Content-Type"), Some("application/json"));
+        let formatted_code = format_http_code("GET https://httpbin.org/ip\ncontent-type: application/json\n");
+        assert!(formatted_code.contains("Content-Type: application/json"));
+        assert_eq!(parse_http_code(&formatted_code), requests);
+    }
+
+    #[test]
+    fn test_format_index_http() {
+        let http_code = std::fs::read_to_string("index.http").unwrap();
+        let formatted_code = format_http_code(&http_code);
+        assert_eq!(parse_http_code(&formatted_code), parse_http_code(&http_code));
+    }
+}
@@ -0,0 +1,235 @@
+use std::path::Path;
+use crate::models::HttpRequest;
+
+/// operation definition in GraphQL document, such as `query GetUser` or anonymous `{ ip }`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operation {
+    pub operation_type: String,
+    pub name: Option<String>,
+}
+
+/// escape text as content of json string, such as value of `{{nick}}` in `{"name": "{{nick}}"}` variables
+pub fn escape_json(text: &str) -> String {
+    let quoted = serde_json::Value::String(text.to_string()).to_string();
+    quoted[1..(quoted.len() - 1)].to_string()
+}
+
+/// split GraphQL body into document and variables json, and variables is the top level block starting with `"` or empty `{}`
+pub fn split_graphql_body(text: &str) -> (String, Option<String>) {
+    let mut offset = 0;
+    for (pos, block_start) in top_level_blocks(text) {
+        if !block_start {
+            continue;
+        }
+        let inner = text[(pos + 1)..].trim_start();
+        if inner.starts_with('"') || inner.starts_with('}') {
+            offset = pos;
+            break;
+        }
+    }
+    if offset == 0 {
+        return (text.trim_end().to_string(), None);
+    }
+    (text[..offset].trim_end().to_string(), Some(text[offset..].trim().to_string()))
+}
+
+/// operations declared in GraphQL document, and fragments are skipped
+pub fn parse_operations(document: &str) -> Vec<Operation> {
+    let mut operations = Vec::new();
+    let mut words: Vec<String> = Vec::new();
+    let mut word_start: Option<usize> = None;
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut in_comment = false;
+    let mut prev = ' ';
+    for (pos, c) in document.char_indices() {
+        if in_comment {
+            in_comment = c != '\n';
+            continue;
+        }
+        if in_string {
+            in_string = !(c == '"' && prev != '\\');
+            prev = c;
+            continue;
+        }
+        prev = c;
+        let word_char = c.is_alphanumeric() || c == '_';
+        if depth == 0 {
+            if word_char {
+                word_start.get_or_insert(pos);
+                continue;
+            } else if let Some(start) = word_start.take() {
+                words.push(document[start..pos].to_string());
+            }
+        }
+        match c {
+            '#' => in_comment = true,
+            '"' => in_string = true,
+            '{' | '(' => {
+                if depth == 0 {
+                    match words.first().map(|word| word.as_str()) {
+                        Some("query" | "mutation" | "subscription") => {
+                            operations.push(Operation { operation_type: words[0].clone(), name: words.get(1).cloned() });
+                        }
+                        None if c == '{' => operations.push(Operation { operation_type: "query".to_owned(), name: None }),
+                        _ => {}
+                    }
+                    words.clear();
+                }
+                depth += 1;
+            }
+            '}' | ')' => {
+                depth -= 1;
+                // variable definitions, such as `($id: ID!)`, are followed by selection set
+                if depth == 0 && c == ')' {
+                    words.push("(".to_owned());
+                }
+            }
+            _ => {}
+        }
+    }
+    operations
+}
+
+/// append fragments of `#import "./fragments.graphql"` lines to GraphQL query, and paths are relative to http file
+pub fn include_fragments(request: &mut HttpRequest, httpfile_dir: &Path) -> std::io::Result<()> {
+    if request.method != "GRAPHQL" {
+        return Ok(());
+    }
+    if let Some(query) = &request.graphql_query {
+        let mut imported_files = Vec::new();
+        let query = resolve_imports(query, httpfile_dir, &mut imported_files)?;
+        request.graphql_query = Some(query);
+        request.body = request.to_graphql_body();
+    }
+    Ok(())
+}
+
+fn resolve_imports(document: &str, base_dir: &Path, imported_files: &mut Vec<std::path::PathBuf>) -> std::io::Result<String> {
+    let mut lines: Vec<&str> = Vec::new();
+    let mut fragments: Vec<String> = Vec::new();
+    for line in document.lines() {
+        match line.trim().strip_prefix("#import") {
+            Some(import) => {
+                let file_name = import.trim().trim_matches(|c| c == '"' || c == '\'');
+                let fragment_file = base_dir.join(file_name);
+                if imported_files.contains(&fragment_file) {
+                    continue;
+                }
+                imported_files.push(fragment_file.clone());
+                let fragment_text = std::fs::read_to_string(&fragment_file)?;
+                let fragment_dir = fragment_file.parent().unwrap_or(base_dir);
+                fragments.push(resolve_imports(&fragment_text, fragment_dir, imported_files)?.trim().to_string());
+            }
+            None => lines.push(line),
+        }
+    }
+    let mut document = lines.join("\n").trim().to_string();
+    for fragment in fragments {
+        document.push_str("\n\n");
+        document.push_str(&fragment);
+    }
+    Ok(document)
+}
+
+/// positions of top level braces, and `true` for block start
+fn top_level_blocks(text: &str) -> Vec<(usize, bool)> {
+    let mut blocks = Vec::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut in_comment = false;
+    let mut prev = ' ';
+    for (pos, c) in text.char_indices() {
+        if in_comment {
+            in_comment = c != '\n';
+        } else if in_string {
+            in_string = !(c == '"' && prev != '\\');
+        } else {
+            match c {
+                '#' => in_comment = true,
+                '"' => in_string = true,
+                '{' => {
+                    if depth == 0 {
+                        blocks.push((pos, true));
+                    }
+                    depth += 1;
+                }
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        blocks.push((pos, false));
+                    }
+                }
+                _ => {}
+            }
+        }
+        prev = c;
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_http_code;
+
+    #[test]
+    fn test_split_graphql_body() {
+        let body = r#"query GetUser($id: ID!) { user(id: $id) { name } }
+{"id": 1}"#;
+        let (query, variables) = split_graphql_body(body);
+        assert_eq!(query, "query GetUser($id: ID!) { user(id: $id) { name } }");
+        assert_eq!(variables.as_deref(), Some(r#"{"id": 1}"#));
+        let (query, variables) = split_graphql_body("{\n  ip\n}");
+        assert_eq!(query, "{\n  ip\n}");
+        assert!(variables.is_none());
+    }
+
+    #[test]
+    fn test_parse_operations() {
+        let document = r#"
+# user queries
+query GetUser($id: ID!) {
+  user(id: $id) { ...UserFields }
+}
+
+mutation { logout }
+
+subscription OnMessage {
+  message(filter: "{ x }") { text }
+}
+
+fragment UserFields on User { name }
+"#;
+        let operations = parse_operations(document);
+        println!("{:?}", operations);
+        assert_eq!(operations.len(), 3);
+        assert_eq!(operations[0], Operation { operation_type: "query".to_owned(), name: Some("GetUser".to_owned()) });
+        assert_eq!(operations[1], Operation { operation_type: "mutation".to_owned(), name: None });
+        assert_eq!(operations[2].name.as_deref(), Some("OnMessage"));
+        assert_eq!(parse_operations("{ ip }")[0].operation_type, "query");
+    }
+
+    #[test]
+    fn test_include_fragments() {
+        let dir = std::env::temp_dir().join("httpfile-graphql");
+        std::fs::create_dir_all(dir.join("fragments")).unwrap();
+        std::fs::write(dir.join("fragments/user.graphql"), "#import \"./avatar.graphql\"\nfragment UserFields on User { name ...AvatarFields }\n").unwrap();
+        std::fs::write(dir.join("fragments/avatar.graphql"), "fragment AvatarFields on User { avatar }\n").unwrap();
+        let http_code = r#"
+### user
+GRAPHQL https://example.com/graphql
+
+#import "./fragments/user.graphql"
+query GetUser { user { ...UserFields } }
+"#;
+        let mut request = parse_http_code(http_code).remove(0);
+        include_fragments(&mut request, &dir).unwrap();
+        let query = request.graphql_query.as_deref().unwrap();
+        println!("{}", query);
+        assert!(query.starts_with("query GetUser"));
+        assert!(query.contains("fragment UserFields on User"));
+        assert!(query.ends_with("fragment AvatarFields on User { avatar }"));
+        assert!(request.body.unwrap().contains("fragment AvatarFields"));
+    }
+}
@@ -0,0 +1,45 @@
+use std::path::{Path, PathBuf};
+use prost::Message;
+use crate::models::HttpRequest;
+
+/// compile `.proto` file into encoded `FileDescriptorSet`, and imports are resolved against directory of the proto file
+pub fn compile_proto_descriptor(proto_file: &Path) -> Result<Vec<u8>, String> {
+    let include_dir = proto_file.parent().unwrap_or_else(|| Path::new("."));
+    let file_name = proto_file.file_name().ok_or_else(|| format!("illegal proto file: {}", proto_file.display()))?;
+    let file_descriptor_set = protox::compile([file_name], [include_dir]).map_err(|e| e.to_string())?;
+    Ok(file_descriptor_set.encode_to_vec())
+}
+
+/// proto file of `@proto` tag for `GRPC` request, and path is relative to http file
+pub fn find_proto_file(request: &HttpRequest, httpfile_dir: &Path) -> Option<PathBuf> {
+    if request.method != "GRPC" {
+        return None;
+    }
+    request.get_tag_value("proto").filter(|proto| !proto.is_empty()).map(|proto| httpfile_dir.join(proto))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_http_code;
+
+    #[test]
+    fn test_compile_proto_descriptor() {
+        let http_code = r#"
+### say hello
+//@proto greeter.proto
+GRPC localhost:50051/helloworld.Greeter/SayHello
+
+{
+  "name": "Rust"
+}
+"#;
+        let requests = parse_http_code(http_code);
+        assert_eq!(requests[0].uri.as_deref(), Some("http://localhost:50051/helloworld.Greeter/SayHello"));
+        let proto_file = find_proto_file(&requests[0], Path::new("../httpfile/fixtures")).unwrap();
+        let descriptor = compile_proto_descriptor(&proto_file).unwrap();
+        let file_descriptor_set = prost_types::FileDescriptorSet::decode(descriptor.as_slice()).unwrap();
+        assert_eq!(file_descriptor_set.file[0].package(), "helloworld");
+        assert!(compile_proto_descriptor(Path::new("../httpfile/fixtures/missing.proto")).is_err());
+    }
+}
@@ -1,16 +1,30 @@
-mod models;
-mod parser;
+pub mod chain;
+pub mod curl;
+pub mod formatter;
+pub mod graphql;
+pub mod grpc;
+pub mod models;
+pub mod openapi;
+pub mod parser;
+pub mod postman;
+pub mod types;
 
 use std::env;
 use std::fs::File;
 use std::io::{Write};
 use std::path::{Path, PathBuf};
+use x509_parser::pem::Pem;
+use x509_parser::prelude::{FromDer, X509Certificate};
 
 #[derive(Debug, Clone)]
 pub struct Builder {
     pub httpfile_path: String,
     pub http_client: String,
     out_dir: Option<PathBuf>,
+    openapi_format: Option<String>,
+    infer_types: bool,
+    expect_status: Option<String>,
+    root_certificates: Vec<PathBuf>,
 }
 
 pub fn configure() -> Builder {
@@ -18,6 +32,10 @@ pub fn configure() -> Builder {
         httpfile_path: "index.http".to_string(),
         http_client: "reqwest".to_string(),
         out_dir: Some(PathBuf::from(env::var("OUT_DIR").unwrap())),
+        openapi_format: None,
+        infer_types: false,
+        expect_status: None,
+        root_certificates: Vec::new(),
     }
 }
 
@@ -30,45 +48,171 @@ impl Builder {
         self.out_dir = Some(out_dir.as_ref().to_path_buf());
         self
     }
+    /// generate OpenAPI document beside the generated Rust file, and format should be `json` or `yaml`
+    pub fn openapi(mut self, format: &str) -> Self {
+        self.openapi_format = Some(format.to_string());
+        self
+    }
+
+    /// infer serde structs from json body and `<> ./response.json`, and generated function takes body struct
+    pub fn infer_types(mut self, infer_types: bool) -> Self {
+        self.infer_types = infer_types;
+        self
+    }
+
+    /// default expected status for requests without `@expect-status`, such as `2xx` or `200,201`
+    pub fn expect_status(mut self, expected: &str) -> Self {
+        self.expect_status = Some(expected.to_string());
+        self
+    }
+
+    /// extra root CA certificate in PEM or DER for generated client, and certificate is embedded with `include_bytes!`
+    pub fn root_certificate(mut self, certificate_path: impl AsRef<Path>) -> Self {
+        self.root_certificates.push(certificate_path.as_ref().to_path_buf());
+        self
+    }
 
     pub fn compile(self) -> std::io::Result<()> {
         let http_file_path = Path::new(&self.httpfile_path);
         let httpfile_text = std::fs::read_to_string(http_file_path)?;
-        let request_targets = parser::parse_http_code(&httpfile_text);
-        let mut file_lines: Vec<String> = Vec::new();
-        file_lines.push("use std::collections::HashMap;".to_owned());
-        file_lines.push("use reqwest::{Client, Response, Result};".to_owned());
-        file_lines.push("use reqwest::header::HeaderMap;".to_owned());
-        file_lines.push("use handlebars::Handlebars;".to_owned());
-        file_lines.push("".to_owned());
+        let mut request_targets = parser::parse_http_code(&httpfile_text);
+        if let Some(expected) = &self.expect_status {
+            for request_target in request_targets.iter_mut() {
+                if request_target.get_tag_value("expect-status").is_none() {
+                    request_target.tags.push(format!("expect-status {}", expected));
+                }
+            }
+        }
+        // include fragments of GraphQL requests, and embed descriptors of `@proto` files for gRPC requests
+        let httpfile_dir = http_file_path.parent().unwrap_or_else(|| Path::new("."));
+        let out_dir = self.out_dir.clone().unwrap();
+        std::fs::create_dir_all(&out_dir)?;
+        for request_target in request_targets.iter_mut() {
+            graphql::include_fragments(request_target, httpfile_dir)?;
+            if let Some(proto_file) = grpc::find_proto_file(request_target, httpfile_dir) {
+                let descriptor = grpc::compile_proto_descriptor(&proto_file).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+                let file_stem = proto_file.file_stem().unwrap().to_str().unwrap();
+                let descriptor_path = std::fs::canonicalize(&out_dir)?.join(format!("{}.desc", file_stem));
+                std::fs::write(&descriptor_path, descriptor)?;
+                request_target.tags.push(format!("descriptor {}", descriptor_path.display()));
+            }
+        }
+        let mut type_codes: Vec<String> = Vec::new();
+        if self.infer_types {
+            type_codes = types::infer_types(&mut request_targets, httpfile_dir).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: {}", http_file_path.display(), e)))?;
+        }
+        for request_target in &request_targets {
+            request_target.validate_assertions().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: {}", http_file_path.display(), e)))?;
+            request_target.validate_response_type().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: {}", http_file_path.display(), e)))?;
+        }
+        // dependencies of `{{name.response.body.$.token}}` references are validated, and dependencies taking params are tagged
+        chain::validate_dependencies(&request_targets).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        let dependency_tags: Vec<Vec<String>> = request_targets
+            .iter()
+            .map(|request_target| {
+                let dependencies = request_target.dependencies();
+                let dependencies = dependencies.iter().filter_map(|name| request_targets.iter().find(|request| &request.name == name));
+                dependencies.map(|dependency| if dependency.is_params_required() { format!("depends-on {} params", dependency.name) } else { format!("depends-on {}", dependency.name) }).collect()
+            })
+            .collect();
+        for (request_target, tags) in request_targets.iter_mut().zip(dependency_tags) {
+            request_target.tags.extend(tags);
+        }
+        let mut file_lines: Vec<String> = vec![
+            "use std::collections::HashMap;".to_owned(),
+            "use reqwest::{Client, Response};".to_owned(),
+            "use reqwest::header::HeaderMap;".to_owned(),
+            "use handlebars::Handlebars;".to_owned(),
+            "".to_owned(),
+            format!("const HTTP_FILE: &str = \"{}\";", http_file_path.file_name().unwrap().to_str().unwrap()),
+            "".to_owned(),
+        ];
         // lazy_static block
         file_lines.push("lazy_static::lazy_static! {".to_owned());
-        file_lines.push("  static ref CLIENT: Client = reqwest::Client::new();".to_owned());
+        let mut client_code = "httpfile::client_builder()".to_owned();
+        let mut certificate_codes: Vec<String> = Vec::new();
+        for certificate_path in &self.root_certificates {
+            let certificate_path = std::fs::canonicalize(certificate_path)?;
+            // certificate is checked at build time, so it is embedded with `unwrap()` alike client building
+            validate_certificate(&std::fs::read(&certificate_path)?)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("invalid root certificate {}: {}", certificate_path.display(), e)))?;
+            let certificate_code = format!("httpfile::tls::root_certificate(include_bytes!({:?})).unwrap()", certificate_path);
+            client_code.push_str(&format!("\n    .add_root_certificate({})", certificate_code));
+            certificate_codes.push(certificate_code);
+        }
+        // clients of `@proxy` tag take root certificates too
+        if request_targets.iter().any(|request_target| request_target.get_tag_value("proxy").is_some()) {
+            file_lines.push(format!("  static ref ROOT_CERTIFICATES: Vec<reqwest::Certificate> = vec![{}];", certificate_codes.join(", ")));
+        }
+        file_lines.push(format!("  static ref CLIENT: Client = {}\n    .build()\n    .unwrap();", client_code));
+        if request_targets.iter().any(|request_target| request_target.get_tag_value("insecure").is_some()) {
+            file_lines.push(format!("  static ref INSECURE_CLIENT: Client = {}\n    .danger_accept_invalid_certs(true)\n    .build()\n    .unwrap();", client_code));
+        }
         file_lines.push("  static ref HANDLEBARS: Handlebars<'static> = {".to_owned());
         file_lines.push("    let mut reg = Handlebars::new();".to_owned());
         // include http body template
-        for request_target in &request_targets {
-            if let Some(body) = &request_target.body {
-                if body.contains("{{") {
-                    let template_name = format!("{}_body", request_target.name);
-                    file_lines.push(format!("    reg.register_template_string(\"{}\", r#\"{}\"#).unwrap();", template_name, body));
-                }
+        for request_target in request_targets.iter().filter(|request_target| request_target.method != "GRAPHQL") {
+            if let Some((template_name, template_text)) = request_target.body_template() {
+                file_lines.push(format!("    reg.register_template_string(\"{}\", r#\"{}\"#).unwrap();", template_name, template_text));
             }
         }
         file_lines.push("    reg".to_owned());
         file_lines.push("    };".to_owned());
+        // GraphQL variables templates escape values as json text
+        let graphql_templates: Vec<(String, String)> = request_targets
+            .iter()
+            .filter(|request_target| request_target.method == "GRAPHQL")
+            .filter_map(|request_target| request_target.body_template())
+            .collect();
+        if !graphql_templates.is_empty() {
+            file_lines.push("  static ref GRAPHQL_HANDLEBARS: Handlebars<'static> = {".to_owned());
+            file_lines.push("    let mut reg = Handlebars::new();".to_owned());
+            file_lines.push("    reg.register_escape_fn(httpfile::graphql::escape_json);".to_owned());
+            for (template_name, template_text) in graphql_templates {
+                file_lines.push(format!("    reg.register_template_string(\"{}\", r#\"{}\"#).unwrap();", template_name, template_text));
+            }
+            file_lines.push("    reg".to_owned());
+            file_lines.push("    };".to_owned());
+        }
         file_lines.push("  }".to_owned());
+        for type_code in type_codes {
+            file_lines.push("".to_owned());
+            file_lines.push(type_code);
+        }
         for request_target in &request_targets {
             file_lines.push("".to_owned());
             file_lines.push(request_target.to_rust_code());
         }
         let rust_file_code = file_lines.join("\n");
         let rust_file_name = http_file_path.file_name().unwrap().to_str().unwrap().replace(".http", ".rs");
-        let dest_path = self.out_dir.unwrap().join(rust_file_name);
+        let dest_path = out_dir.join(rust_file_name);
         println!("dest_path = {:?}", dest_path);
         let mut file = File::create(dest_path)?;
-        file.write_all(rust_file_code.as_bytes())
+        file.write_all(rust_file_code.as_bytes())?;
+        if let Some(openapi_format) = &self.openapi_format {
+            let file_stem = http_file_path.file_stem().unwrap().to_str().unwrap();
+            let (openapi_text, extension) = match openapi_format.as_str() {
+                "yaml" | "yml" => (openapi::to_openapi_yaml(&request_targets, file_stem), "yaml"),
+                _ => (openapi::to_openapi_json(&request_targets, file_stem), "json"),
+            };
+            std::fs::write(out_dir.join(format!("{}.openapi.{}", file_stem, extension)), openapi_text)?;
+        }
+        Ok(())
+    }
+}
+
+/// check certificate is X.509 in PEM or DER, and the first `CERTIFICATE` block of PEM is parsed
+fn validate_certificate(certificate: &[u8]) -> Result<(), String> {
+    if !String::from_utf8_lossy(certificate).contains("-----BEGIN CERTIFICATE-----") {
+        return X509Certificate::from_der(certificate).map(|_| ()).map_err(|e| format!("certificate should be PEM or DER: {}", e));
     }
+    for pem in Pem::iter_from_buffer(certificate) {
+        let pem = pem.map_err(|e| format!("illegal PEM certificate: {}", e))?;
+        if pem.label == "CERTIFICATE" {
+            return pem.parse_x509().map(|_| ()).map_err(|e| format!("illegal PEM certificate: {}", e));
+        }
+    }
+    Err("CERTIFICATE block missing".to_owned())
 }
 
 #[cfg(test)]
@@ -81,8 +225,38 @@ mod tests {
         env::set_var("OUT_DIR", path.to_str().unwrap());
         configure()
             .httpfile("index.http")
+            .openapi("yaml")
+            .compile()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_root_certificate() {
+        let path = env::current_dir().unwrap().join("temp");
+        env::set_var("OUT_DIR", path.to_str().unwrap());
+        let out_dir = path.join("tls");
+        configure()
+            .httpfile("index.http")
+            .out_dir(&out_dir)
+            .root_certificate("../httpfile/fixtures/client.pem")
             .compile()
             .unwrap();
+        let rust_code = std::fs::read_to_string(out_dir.join("index.rs")).unwrap();
+        assert!(rust_code.contains(".add_root_certificate(httpfile::tls::root_certificate(include_bytes!("));
+        assert!(rust_code.contains("    reg.register_escape_fn(httpfile::graphql::escape_json);\n    reg.register_template_string(\"graphql_demo_variables\""));
+        // clients of `@proxy` tag take the same root certificates
+        let proxy_http_file = out_dir.join("proxy.http");
+        std::fs::write(&proxy_http_file, "### my ip\n//@name my-ip\n//@proxy socks5://127.0.0.1:1080\nGET https://httpbin.org/ip\n").unwrap();
+        configure().httpfile(&proxy_http_file).out_dir(&out_dir).root_certificate("../httpfile/fixtures/client.pem").compile().unwrap();
+        let rust_code = std::fs::read_to_string(out_dir.join("proxy.rs")).unwrap();
+        assert!(rust_code.contains("  static ref ROOT_CERTIFICATES: Vec<reqwest::Certificate> = vec![httpfile::tls::root_certificate(include_bytes!("));
+        assert!(rust_code.contains(r##"let client = httpfile::proxy::client(location, r#"socks5://127.0.0.1:1080"#, false, &ROOT_CERTIFICATES)?;"##));
+        let invalid_certificate = path.join("invalid.pem");
+        std::fs::write(&invalid_certificate, "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n").unwrap();
+        let error = configure().httpfile("index.http").out_dir(&out_dir).root_certificate(&invalid_certificate).compile().unwrap_err();
+        println!("{}", error);
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(validate_certificate(b"not a certificate").is_err());
     }
 }
 
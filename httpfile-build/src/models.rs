@@ -1,4 +1,7 @@
-#[derive(Debug)]
+use crate::chain::{self, ResponseReference};
+use crate::graphql;
+
+#[derive(Debug, Clone)]
 pub struct HttpHeader {
     pub name: String,
     pub value: String,
@@ -13,14 +16,73 @@ impl HttpHeader {
     }
 }
 
-#[derive(Debug)]
+impl PartialEq for HttpHeader {
+    fn eq(&self, other: &Self) -> bool {
+        // header names are case-insensitive
+        self.name.eq_ignore_ascii_case(&other.name) && self.value == other.value
+    }
+}
+
+/// operators of `> assert` lines, and `exists` takes no expected value
+const ASSERTION_OPERATORS: [&str; 8] = ["==", "!=", ">=", "<=", ">", "<", "contains", "exists"];
+
+/// declarative assertion of `> assert status == 200` line in response handler
+#[derive(Debug, Clone)]
+pub struct HttpAssertion {
+    pub line_number: u32,
+    pub expression: String,
+}
+
+impl HttpAssertion {
+    pub fn new(line_number: u32, expression: String) -> HttpAssertion {
+        HttpAssertion { line_number, expression }
+    }
+
+    /// subject, operator and expected value, such as `header Content-Type`, `contains` and `json`,
+    /// and operator in brackets of JSONPath, such as `$.items[?@.price < 10]`, is skipped
+    pub fn parse(&self) -> Result<(String, String, String), String> {
+        let mut depth = 0;
+        let mut offset = 0;
+        for token in self.expression.split_whitespace() {
+            let start = offset + self.expression[offset..].find(token).unwrap();
+            offset = start + token.len();
+            if depth == 0 && start > 0 && ASSERTION_OPERATORS.contains(&token) {
+                let subject = self.expression[..start].trim();
+                let expected = self.expression[offset..].trim();
+                let expected = expected.strip_prefix('"').and_then(|text| text.strip_suffix('"')).unwrap_or(expected);
+                let subject_legal = ["status", "body"].contains(&subject) || subject.starts_with("header ") || subject.starts_with('$') || subject.starts_with('/');
+                return if !subject_legal {
+                    Err(format!("line {}: unknown assertion subject `{}`", self.line_number, subject))
+                } else if (token == "exists") != expected.is_empty() {
+                    Err(format!("line {}: illegal expected value for `{}` in `{}`", self.line_number, token, self.expression))
+                } else {
+                    Ok((subject.to_string(), token.to_string(), expected.to_string()))
+                };
+            }
+            depth += token.matches(['[', '(']).count() as i32 - token.matches([']', ')']).count() as i32;
+        }
+        Err(format!("line {}: operator not found in assertion `{}`", self.line_number, self.expression))
+    }
+}
+
+impl PartialEq for HttpAssertion {
+    fn eq(&self, other: &Self) -> bool {
+        self.expression == other.expression
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct HttpRequest {
     pub index: u32,
     pub comment: Option<String>,
+    pub comments: Vec<String>,
+    /// `//` comments between headers, with count of headers before each comment
+    pub header_comments: Vec<(usize, String)>,
     pub tags: Vec<String>,
     pub name: String,
     pub method: String,
     pub request_line: String,
+    pub request_line_number: u32,
     pub path: String,
     pub protocol: Option<String>,
     pub uri: Option<String>,
@@ -29,8 +91,16 @@ pub struct HttpRequest {
     pub body_lines: Vec<String>,
     pub line_numbers: Vec<u32>,
     pub body: Option<String>,
+    pub body_type: Option<String>,
     pub js_code: Option<String>,
+    pub js_file: Option<String>,
     pub redirect_response: Option<String>,
+    pub response_refs: Vec<String>,
+    pub assertions: Vec<HttpAssertion>,
+    pub graphql_query: Option<String>,
+    pub graphql_variables: Option<String>,
+    pub jsonrpc_method: Option<String>,
+    pub jsonrpc_params: Option<String>,
     pub variable_names: Vec<String>,
 }
 
@@ -39,10 +109,13 @@ impl HttpRequest {
         HttpRequest {
             index,
             comment: None,
+            comments: Vec::new(),
+            header_comments: Vec::new(),
             tags: Vec::new(),
             name: format!("http{}", index),
             method: String::new(),
             request_line: String::new(),
+            request_line_number: 0,
             path: String::new(),
             protocol: None,
             uri: None,
@@ -51,8 +124,16 @@ impl HttpRequest {
             body_lines: Vec::new(),
             line_numbers: Vec::new(),
             body: None,
+            body_type: None,
             js_code: None,
+            js_file: None,
             redirect_response: None,
+            response_refs: Vec::new(),
+            assertions: Vec::new(),
+            graphql_query: None,
+            graphql_variables: None,
+            jsonrpc_method: None,
+            jsonrpc_params: None,
             variable_names: Vec::new(),
         }
     }
@@ -71,27 +152,53 @@ impl HttpRequest {
 
     pub fn get_header_value(&self, name: &str) -> Option<&str> {
         for header in &self.headers {
-            if header.name == name {
+            if header.name.eq_ignore_ascii_case(name) {
                 return Some(&header.value);
             }
         }
         None
     }
 
+    /// value of tag, such as `600` for `//@timeout 600`, and empty text for tag without value
+    pub fn get_tag_value(&self, name: &str) -> Option<&str> {
+        for tag in &self.tags {
+            if let Some(value) = tag.strip_prefix(name) {
+                if value.is_empty() {
+                    return Some(value);
+                } else if value.starts_with(' ') || value.starts_with('=') {
+                    return Some(value[1..].trim());
+                }
+            }
+        }
+        None
+    }
+
     pub fn cleanup_metadata(&mut self) {
         let parts = self.request_line.split_whitespace().collect::<Vec<&str>>();
         if !parts.is_empty() {
             self.path = parts[0].to_string();
-            if self.path.starts_with("http://") || self.path.starts_with("https://") {
+            if ["http://", "https://", "ws://", "wss://"].iter().any(|scheme| self.path.starts_with(scheme)) {
                 self.uri = Some(self.path.clone());
+            } else if self.method == "GRPC" {
+                // gRPC target alike `localhost:50051/helloworld.Greeter/SayHello`, and `grpcs://` for TLS
+                self.uri = Some(if let Some(target) = self.path.strip_prefix("grpcs://") {
+                    format!("https://{}", target)
+                } else {
+                    format!("http://{}", self.path.trim_start_matches("grpc://"))
+                });
             }
         }
         if parts.len() > 1 {
-            self.protocol = Some(parts[1].to_string());
+            // method name of JSON-RPC is after url, such as `JSONRPC http://localhost/rpc eth_getBalance`
+            if self.method == "JSONRPC" {
+                self.jsonrpc_method = Some(parts[1].to_string());
+            } else {
+                self.protocol = Some(parts[1].to_string());
+            }
         }
         if self.uri.is_none() {
             if is_http_method(&self.method) {
-                if let Some(host) = self.get_header_value("Host") { self.uri = Some(format!("http://{}", host)); }
+                if let Some(host) = self.get_header_value("Host") { self.uri = Some(format!("http://{}{}", host, self.path)); }
             } else if let Some(uri) = self.get_header_value("URI") {
                 self.uri = Some(uri.to_string());
             }
@@ -102,7 +209,9 @@ impl HttpRequest {
         if !self.body_lines.is_empty() {
             let mut lines: Vec<String> = Vec::new();
             for line in self.body_lines.iter() {
-                if !line.starts_with("<>") {
+                if let Some(response_ref) = line.strip_prefix("<>") {
+                    self.response_refs.push(response_ref.trim().to_string());
+                } else {
                     lines.push(line.clone());
                 }
             }
@@ -126,7 +235,7 @@ impl HttpRequest {
             let mut i = 0;
             while i < lines.len() {
                 if lines[i].starts_with("> ") && lines[i].ends_with(".js") {
-                    lines.remove(i);
+                    self.js_file = Some(lines.remove(i)[2..].trim().to_string());
                 } else if lines[i].starts_with(">> ") || lines[i].starts_with(">>! ") {
                     self.redirect_response = Some(lines.remove(i));
                 } else {
@@ -134,40 +243,131 @@ impl HttpRequest {
                 }
             }
             //clean tail empty line
-            while lines.len() > 0 && lines[lines.len() - 1].is_empty() {
+            while lines.last().is_some_and(|line| line.is_empty()) {
                 lines.pop();
             }
             // set http body
             if !lines.is_empty() {
                 if self.method == "GRAPHQL" {
-                    let json_offset = lines.iter().position(|r| r == "{").unwrap_or(0);
-                    let last_line = lines.get(lines.len() - 1).unwrap();
-                    let mut doc = String::new();
-                    doc.push_str(r#"{"query": ""#);
-                    if json_offset > 0 && last_line == "}" {
-                        let query = lines[0..json_offset].join("\n");
-                        let variables = lines[json_offset..].join("\n");
-                        doc.push_str(query.replace("\"", "\\\"").replace("\n", "\\n").as_str());
-                        doc.push_str("\"");
-                        doc.push_str(r#","variables": "#);
-                        doc.push_str(&variables);
-                        doc.push_str("\n}");
-                    } else {
-                        let query = lines.join("\n");
-                        doc.push_str(query.replace("\"", "\\\"").replace("\n", "\\n").as_str());
-                        doc.push_str("\"}");
-                    };
-                    self.body = Some(doc);
+                    let (query, variables) = graphql::split_graphql_body(&lines.join("\n"));
+                    self.graphql_query = Some(query);
+                    self.graphql_variables = variables;
+                    self.body = self.to_graphql_body();
+                } else if self.method == "JSONRPC" {
+                    self.jsonrpc_params = Some(lines.join("\n"));
                 } else {
                     self.body = Some(lines.join("\n"));
                 }
             }
         }
+        if self.method == "JSONRPC" {
+            self.body = self.to_jsonrpc_body();
+        }
+    }
+
+    /// GraphQL request body built by serde, and variables with unquoted placeholders, such as `{{id}}`, are kept as text
+    pub fn to_graphql_body(&self) -> Option<String> {
+        let query = self.graphql_query.as_ref()?;
+        let mut doc = serde_json::Map::new();
+        doc.insert("query".to_owned(), serde_json::Value::String(query.clone()));
+        if let Some(operation_name) = self.get_tag_value("operation-name") {
+            doc.insert("operationName".to_owned(), serde_json::Value::String(operation_name.to_string()));
+        }
+        let variables = match self.graphql_variables.as_deref().map(str::trim).filter(|variables| !variables.is_empty()) {
+            Some(variables) => variables,
+            None => return Some(serde_json::Value::Object(doc).to_string()),
+        };
+        match serde_json::from_str::<serde_json::Value>(variables) {
+            Ok(json) => {
+                doc.insert("variables".to_owned(), json);
+                Some(serde_json::Value::Object(doc).to_string())
+            }
+            Err(_) => {
+                let doc_text = serde_json::Value::Object(doc).to_string();
+                Some(format!(r#"{},"variables":{}}}"#, &doc_text[..(doc_text.len() - 1)], variables))
+            }
+        }
+    }
+
+    /// GraphQL operation selected by `@operation-name` tag, or the only operation in document
+    pub fn graphql_operation(&self) -> Option<graphql::Operation> {
+        let operations = graphql::parse_operations(self.graphql_query.as_deref().unwrap_or_default());
+        match self.get_tag_value("operation-name") {
+            Some(operation_name) => operations.into_iter().find(|operation| operation.name.as_deref() == Some(operation_name)),
+            None if operations.len() == 1 => operations.into_iter().next(),
+            None => None,
+        }
+    }
+
+    /// operation name is taken by generated function if GraphQL document has several operations without `@operation-name` tag
+    pub fn is_operation_name_required(&self) -> bool {
+        self.method == "GRAPHQL"
+            && self.get_tag_value("operation-name").is_none()
+            && graphql::parse_operations(self.graphql_query.as_deref().unwrap_or_default()).len() > 1
+    }
+
+    /// JSON-RPC 2.0 envelope with body as params, and calls are separated by `=== method` lines for batch request
+    fn to_jsonrpc_body(&self) -> Option<String> {
+        let first_id = self.get_tag_value("jsonrpc-id").and_then(|id| id.parse::<u64>().ok()).unwrap_or(1);
+        let params = self.jsonrpc_params.as_deref().unwrap_or_default();
+        if !self.is_jsonrpc_batch() {
+            return self.jsonrpc_method.as_ref().map(|method| to_jsonrpc_envelope(first_id, method, params));
+        }
+        let mut calls: Vec<(String, Vec<&str>)> = Vec::new();
+        for line in params.lines() {
+            if let Some(method) = line.strip_prefix("===") {
+                calls.push((method.trim().to_string(), Vec::new()));
+            } else if let Some((_, params_lines)) = calls.last_mut() {
+                params_lines.push(line);
+            }
+        }
+        let envelopes = calls
+            .iter()
+            .enumerate()
+            .map(|(i, (method, params_lines))| to_jsonrpc_envelope(first_id + i as u64, method, params_lines.join("\n").trim()))
+            .collect::<Vec<String>>();
+        Some(format!("[\n{}\n]", envelopes.join(",\n")))
+    }
+
+    /// `PUB` or `SUB` request for message broker in `URI` header
+    pub fn is_broker_request(&self) -> bool {
+        self.method == "PUB" || self.method == "SUB"
+    }
+
+    /// `SHELL` request with interpreter in request line, such as `SHELL bash`, or `SSH` request with `user@host:port`
+    pub fn is_command_request(&self) -> bool {
+        self.method == "SHELL" || self.method == "SSH"
+    }
+
+    /// batch JSON-RPC request without method name in request line, and body starts with `=== method`
+    pub fn is_jsonrpc_batch(&self) -> bool {
+        self.method == "JSONRPC" && self.jsonrpc_method.is_none() && self.jsonrpc_params.as_ref().is_some_and(|params| params.starts_with("==="))
     }
 
     pub fn to_rust_code(&self) -> String {
         let mut code_lines: Vec<String> = Vec::new();
         let mut variables_included = false;
+        let request_name = self.get_tag_value("name").unwrap_or(&self.name);
+        code_lines.push(format!(r#"  let location = &httpfile::RequestLocation::new("{}", HTTP_FILE, {});"#, request_name, self.request_line_number));
+        // OAuth2 tokens for `{{$auth.token("name")}}` are merged into params
+        let auth_variables = self.auth_variable_names();
+        if !auth_variables.is_empty() {
+            let auth_variables = auth_variables.iter().map(|name| format!("{:?}", name)).collect::<Vec<String>>().join(", ");
+            code_lines.push(format!("  let auth_tokens = httpfile::oauth2::fetch_tokens(location, &[{}]).await?;", auth_variables));
+            code_lines.push("  let params = &httpfile::oauth2::merge_params(params, &auth_tokens);".to_owned());
+        }
+        // responses of dependencies are taken as arguments, and referenced values are merged into params
+        let response_references = self.response_references();
+        if !response_references.is_empty() {
+            variables_included = true;
+            let references = response_references
+                .iter()
+                .map(|reference| format!("({:?}, {}_response)", reference.variable, reference.request_name))
+                .collect::<Vec<String>>()
+                .join(", ");
+            code_lines.push(format!("  let references = httpfile::chain::resolve(location, &[{}])?;", references));
+            code_lines.push("  let params = &httpfile::chain::merge_params(params, &references);".to_owned());
+        }
         //url code
         if let Some(uri) = &self.uri {
             if uri.contains("{{") {
@@ -178,55 +378,625 @@ impl HttpRequest {
             }
         }
         // headers
+        let headers_offset = code_lines.len();
         code_lines.push("  let mut headers = HeaderMap::new();".to_owned());
+        let mut digest_auth = false;
         for header in &self.headers {
             let name = &header.name;
-            if self.method != "GRAPHQL" && name == "Content-Type" {
+            if (self.method == "GRAPHQL" || self.method == "JSONRPC") && name.eq_ignore_ascii_case("Content-Type") {
+                continue;
+            }
+            // broker address of `PUB` and `SUB` requests is not sent as header
+            if self.is_broker_request() && name.eq_ignore_ascii_case("URI") {
                 continue;
             }
             let value = &header.value;
+            // digest credentials are answered after challenge instead of sent as header
+            if name.eq_ignore_ascii_case("Authorization") && value.starts_with("Digest ") {
+                digest_auth = true;
+                if value.contains("{{") {
+                    variables_included = true;
+                    code_lines.push(format!("  let digest_auth = {};", to_place_holder(value)));
+                } else {
+                    code_lines.push(format!("  let digest_auth = r#\"{}\"#;", value));
+                }
+                continue;
+            }
             if value.contains("{{") {
                 variables_included = true;
-                code_lines.push(format!(r#"  headers.insert("{}", {}.parse().unwrap());"#, header.name, to_place_holder(value)));
+                code_lines.push(format!(r#"  httpfile::insert_header(location, &mut headers, "{}", &{})?;"#, header.name, to_place_holder(value)));
             } else {
-                code_lines.push(format!(r#"  headers.insert("{}", "{}".parse().unwrap());"#, header.name, header.value));
+                code_lines.push(format!("  httpfile::insert_header(location, &mut headers, \"{}\", r#\"{}\"#)?;", header.name, header.value));
             }
         }
-        if self.method == "GRAPHQL" {
-            code_lines.push(r#"headers.insert("Content-Type", "application/json".parse().unwrap());"#.to_owned());
+        if self.method == "GRAPHQL" || self.method == "JSONRPC" {
+            code_lines.push(r#"  httpfile::insert_header(location, &mut headers, "Content-Type", "application/json")?;"#.to_owned());
         }
-        // http body for POST, PUT and GRAPHQL
-        if self.method == "POST" || self.method == "PUT" || self.method == "GRAPHQL" {
-            if let Some(body) = &self.body {
-                if body.contains("{{") {
-                    variables_included = true;
-                    let template_name = format!("{}_body", self.name);
-                    code_lines.push(format!(r#"  let body = HANDLEBARS.render("{}", &params).unwrap();"#, template_name));
-                } else {
-                    code_lines.push(format!("  let body = r#\"{}\"#;", body));
+        if !code_lines[headers_offset..].iter().any(|line| line.contains("&mut headers")) {
+            code_lines[headers_offset] = "  let headers = HeaderMap::new();".to_owned();
+        }
+        // http body
+        if let Some(body) = &self.body {
+            if self.method == "GRAPHQL" {
+                // only variables are rendered by template, and query is kept as it is
+                let query = self.graphql_query.as_deref().unwrap_or_default();
+                let operation_name_code = match self.get_tag_value("operation-name") {
+                    Some(operation_name) => format!("Some({:?})", operation_name),
+                    None if self.is_operation_name_required() => "Some(operation_name)".to_owned(),
+                    None => "None".to_owned(),
+                };
+                let variables_code = match &self.graphql_variables {
+                    Some(variables) if variables.contains("{{") => {
+                        variables_included = true;
+                        let template_name = format!("{}_variables", self.name);
+                        let variable_names = find_variable_names(variables).iter().map(|name| format!("{:?}", name)).collect::<Vec<String>>().join(", ");
+                        // values are escaped as json text by `GRAPHQL_HANDLEBARS` instead of html escaping
+                        code_lines.push(format!(r#"  let variables = httpfile::render_template(location, &GRAPHQL_HANDLEBARS, "{}", params, &[{}])?;"#, template_name, variable_names));
+                        "Some(&variables)".to_owned()
+                    }
+                    Some(variables) => format!("Some({:?})", variables),
+                    None => "None".to_owned(),
+                };
+                code_lines.push(format!("  let body = httpfile::graphql::to_body(location, {:?}, {}, {})?;", query, operation_name_code, variables_code));
+            } else if self.body_type.is_some() {
+                code_lines.push("  let body = httpfile::to_json(location, body)?;".to_owned());
+            } else if self.is_body_template() {
+                variables_included = true;
+                let template_name = format!("{}_body", self.name);
+                let variable_names = find_variable_names(body).iter().map(|name| format!("{:?}", name)).collect::<Vec<String>>().join(", ");
+                code_lines.push(format!(r#"  let body = httpfile::render_template(location, &HANDLEBARS, "{}", params, &[{}])?;"#, template_name, variable_names));
+            } else if body.contains("{{") {
+                variables_included = true;
+                code_lines.push(format!("  let body = {};", to_place_holder(body)));
+            } else {
+                code_lines.push(format!("  let body = r#\"{}\"#;", body));
+            }
+        }
+        // outgoing messages are split from body, and incoming messages are returned as stream
+        if self.method == "WEBSOCKET" {
+            let messages_code = if self.body.is_some() { "httpfile::websocket::parse_messages(&body)" } else { "Vec::new()" };
+            code_lines.push(format!("  httpfile::websocket::connect(location, &url, headers, {}).await", messages_code));
+            return self.to_function_code(code_lines, variables_included, "httpfile::websocket::MessageStream");
+        }
+        // message broker is selected by URI scheme, such as `mqtt://` or `nats://`, and topic is from request line
+        if self.is_broker_request() {
+            let url_code = if self.uri.is_some() { "&url" } else { r#""""# };
+            let topic_code = if self.path.contains("{{") {
+                variables_included = true;
+                format!("&{}", to_place_holder(&self.path))
+            } else {
+                format!("{:?}", self.path)
+            };
+            if self.method == "SUB" {
+                code_lines.truncate(headers_offset);
+                code_lines.push(format!("  httpfile::broker::subscribe(location, {}, {}).await", url_code, topic_code));
+                return self.to_function_code(code_lines, variables_included, "httpfile::broker::MessageStream");
+            }
+            let body_code = if self.body.is_some() { "&body" } else { r#""""# };
+            code_lines.push(format!("  httpfile::broker::publish(location, {}, {}, headers, {}).await", url_code, topic_code, body_code));
+            return self.to_function_code(code_lines, variables_included, "()");
+        }
+        // script in body is run by interpreter or on remote host, and stdout is decoded with `@response-type`
+        if self.is_command_request() {
+            let target_code = if self.path.contains("{{") {
+                variables_included = true;
+                format!("&{}", to_place_holder(&self.path))
+            } else {
+                format!("{:?}", self.path)
+            };
+            let body_code = if self.body.is_some() { "&body" } else { r#""""# };
+            // host keys not in `~/.ssh/known_hosts` are accepted only for `@insecure` requests
+            let command_code = if self.method == "SSH" {
+                let insecure = self.get_tag_value("insecure").is_some();
+                format!("httpfile::shell::ssh(location, {}, {}, &headers, {}).await", target_code, insecure, body_code)
+            } else {
+                format!("httpfile::shell::run(location, {}, &headers, {}).await", target_code, body_code)
+            };
+            let response_type = self.get_tag_value("response-type");
+            let assertions_code = self.to_assertions_code();
+            if response_type.is_some() || assertions_code.is_some() {
+                code_lines.push(format!("  let output = {}?;", command_code));
+            }
+            // stdout is checked alike response body, and exit status alike response status
+            if let Some(assertions_code) = &assertions_code {
+                code_lines.push(format!("  httpfile::assertion::check(location, &httpfile::chain::CapturedResponse::from(output.clone()), {})?;", assertions_code));
+            }
+            let return_type = match response_type.map(to_response_rust_type) {
+                Some("String") => {
+                    code_lines.push("  Ok(output.text().to_owned())".to_owned());
+                    "String"
+                }
+                Some("Vec<u8>") => {
+                    code_lines.push("  Ok(output.stdout.into_bytes())".to_owned());
+                    "Vec<u8>"
+                }
+                Some(rust_type) => {
+                    code_lines.push(format!("  output.json::<{}>(location)", rust_type));
+                    rust_type
                 }
+                None => {
+                    if assertions_code.is_some() {
+                        code_lines.push("  Ok(output)".to_owned());
+                    } else {
+                        code_lines.push(format!("  {}", command_code));
+                    }
+                    "httpfile::shell::CommandOutput"
+                }
+            };
+            return self.to_function_code(code_lines, variables_included, return_type);
+        }
+        // GraphQL subscription over WebSocket, and payloads of `next` messages are returned as stream
+        if self.method == "GRAPHQL" && self.graphql_operation().is_some_and(|operation| operation.operation_type == "subscription") {
+            code_lines.push("  httpfile::graphql::subscribe(location, &url, headers, &body).await".to_owned());
+            return self.to_function_code(code_lines, variables_included, "httpfile::graphql::SubscriptionStream");
+        }
+        // gRPC unary call with json body, and descriptors from `@proto` file are embedded by Builder
+        if self.method == "GRPC" {
+            let body_code = if self.body.is_some() { "&body" } else { r#""{}""# };
+            let descriptor_code = match self.get_tag_value("descriptor") {
+                Some(descriptor) => format!("Some(include_bytes!({:?}).as_slice())", descriptor),
+                None => "None".to_owned(),
+            };
+            // `text` and `bytes` are rejected by Builder, because json output is decoded
+            let response_type = to_response_rust_type(self.get_tag_value("response-type").unwrap_or("json"));
+            // json output is checked alike response body, and decoded after assertions passed
+            if let Some(assertions_code) = self.to_assertions_code() {
+                code_lines.push(format!("  let json = httpfile::grpc::call::<serde_json::Value>(location, &url, headers, {}, {}).await?;", body_code, descriptor_code));
+                code_lines.push(format!("  httpfile::assertion::check(location, &httpfile::chain::CapturedResponse::from(json.clone()), {})?;", assertions_code));
+                code_lines.push("  httpfile::grpc::decode(location, json)".to_owned());
+            } else {
+                code_lines.push(format!("  httpfile::grpc::call::<{}>(location, &url, headers, {}, {}).await", response_type, body_code, descriptor_code));
             }
+            return self.to_function_code(code_lines, variables_included, response_type);
+        }
+        // `@insecure` requests skip host certificate verification, and `@proxy` requests use client with the proxy
+        let insecure = self.get_tag_value("insecure").is_some();
+        let client = if let Some(proxy) = self.get_tag_value("proxy") {
+            let proxy_code = if proxy.contains("{{") {
+                variables_included = true;
+                format!("&{}", to_place_holder(proxy))
+            } else {
+                format!("r#\"{}\"#", proxy)
+            };
+            code_lines.push(format!("  let client = httpfile::proxy::client(location, {}, {}, &ROOT_CERTIFICATES)?;", proxy_code, insecure));
+            "client"
+        } else if insecure {
+            "INSECURE_CLIENT"
+        } else {
+            "CLIENT"
+        };
+        let mut request_code = match self.method.as_str() {
+            "GET" | "POST" | "PUT" | "PATCH" | "DELETE" | "HEAD" => format!("{}.{}(url)", client, self.method.to_lowercase()),
+            "GRAPHQL" | "JSONRPC" => format!("{}.post(url)", client),
+            method => format!("{}.request(reqwest::Method::{}, url)", client, method),
+        };
+        request_code.push_str(".headers(headers)");
+        if self.body.is_some() {
+            request_code.push_str(".body(body)");
         }
-        if self.method == "GET" {
-            code_lines.push("  CLIENT.get(url).headers(headers).send().await".to_owned());
-        } else if self.method == "POST" || self.method == "GRAPHQL" {
-            code_lines.push("  CLIENT.post(url).headers(headers).body(body).send().await".to_owned());
+        // `@sse` requests return events of `text/event-stream` response
+        if self.get_tag_value("sse").is_some() {
+            code_lines.push(format!("  httpfile::sse::connect(location, {}).await", request_code));
+            return self.to_function_code(code_lines, variables_included, "httpfile::sse::EventStream");
+        }
+        let send_code = if digest_auth {
+            format!("httpfile::send_with_digest_auth(location, {}, &digest_auth).await", request_code)
+        } else {
+            format!("httpfile::send(location, {}).await", request_code)
+        };
+        let response_type = self.get_tag_value("response-type");
+        let jsonrpc = self.method == "JSONRPC";
+        let expected_status = self.get_tag_value("expect-status").map(|value| {
+            value
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|pattern| !pattern.is_empty())
+                .map(|pattern| format!(r#""{}""#, pattern))
+                .collect::<Vec<String>>()
+        });
+        let assertions_code = self.to_assertions_code();
+        let checked = expected_status.is_some() || assertions_code.is_some();
+        if response_type.is_none() && !checked && !jsonrpc {
+            code_lines.push(format!("  {}", send_code));
+        } else {
+            code_lines.push(format!("  let response = {}?;", send_code));
+        }
+        // check response status with `@expect-status`
+        if let Some(expected_status) = expected_status {
+            code_lines.push(format!("  let response = httpfile::expect_status(location, response, &[{}]).await?;", expected_status.join(", ")));
+        }
+        // check `> assert` lines before response body is decoded
+        if let Some(assertions_code) = &assertions_code {
+            code_lines.push(format!("  let response = httpfile::assertion::check_response(location, response, {}).await?;", assertions_code));
+        }
+        if checked && response_type.is_none() && !jsonrpc {
+            code_lines.push("  Ok(response)".to_owned());
+        }
+        // decode response body with `@response-type`
+        let return_type = match response_type {
+            // `result` is unwrapped from JSON-RPC response, and `error` is returned as `JsonRpc` error
+            _ if self.is_jsonrpc_batch() => {
+                code_lines.push("  httpfile::jsonrpc::decode_batch(location, response).await".to_owned());
+                "Vec<httpfile::jsonrpc::RpcResult>".to_owned()
+            }
+            _ if jsonrpc => {
+                // `result` of `text` type is json string, such as `eth_blockNumber`
+                let rust_type = to_response_rust_type(response_type.unwrap_or("json"));
+                code_lines.push(format!("  httpfile::jsonrpc::decode_result::<{}>(location, response).await", rust_type));
+                rust_type.to_owned()
+            }
+            Some(response_type) => {
+                let rust_type = to_response_rust_type(response_type);
+                let decode_code = match rust_type {
+                    "String" => "httpfile::decode_text(location, response).await".to_owned(),
+                    "Vec<u8>" => "httpfile::decode_bytes(location, response).await".to_owned(),
+                    rust_type => format!("httpfile::decode_json::<{}>(location, response).await", rust_type),
+                };
+                code_lines.push(format!("  {}", decode_code));
+                rust_type.to_owned()
+            }
+            None => "Response".to_owned(),
+        };
+        self.to_function_code(code_lines, variables_included, &return_type)
+    }
+
+    /// assertions of `> assert` lines for generated code, and illegal assertions are reported by Builder
+    fn to_assertions_code(&self) -> Option<String> {
+        let assertions = self
+            .assertions
+            .iter()
+            .filter_map(|assertion| assertion.parse().ok().map(|parts| (assertion.line_number, parts)))
+            .map(|(line_number, (subject, operator, expected))| {
+                format!("httpfile::assertion::Assertion::new({}, {:?}, {:?}, {:?})", line_number, subject, operator, expected)
+            })
+            .collect::<Vec<String>>();
+        if assertions.is_empty() {
+            None
         } else {
-            code_lines.push(format!("  CLIENT.{}(url).headers(headers).body(body).send().await", self.method));
+            Some(format!("&[{}]", assertions.join(", ")))
+        }
+    }
+
+    /// check `@response-type`, and `GRPC` output is json which can't be returned as `text` or `bytes`
+    pub fn validate_response_type(&self) -> Result<(), String> {
+        match self.get_tag_value("response-type") {
+            Some(response_type) if self.method == "GRPC" && ["String", "Vec<u8>"].contains(&to_response_rust_type(response_type)) => Err(format!(
+                "line {}: `@response-type {}` is not supported for GRPC request `{}`, and json or struct type should be used",
+                self.request_line_number, response_type, self.name
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// check `> assert` lines, and assertions are only checked for http responses and command output
+    pub fn validate_assertions(&self) -> Result<(), String> {
+        let Some(assertion) = self.assertions.first() else {
+            return Ok(());
+        };
+        let streaming = self.get_tag_value("sse").is_some()
+            || ["WEBSOCKET", "SUB", "PUB"].contains(&self.method.as_str())
+            || self.graphql_operation().is_some_and(|operation| operation.operation_type == "subscription");
+        if streaming {
+            return Err(format!("line {}: assertions are not supported for {} request `{}`", assertion.line_number, self.method, self.name));
         }
+        for assertion in &self.assertions {
+            assertion.parse()?;
+        }
+        Ok(())
+    }
+
+    /// wrap code lines into async function, and params or body struct are taken if needed.
+    /// request with dependencies is generated as `{name}_with` function taking captured responses,
+    /// and `{name}` function runs dependencies before it
+    fn to_function_code(&self, mut code_lines: Vec<String>, variables_included: bool, return_type: &str) -> String {
         code_lines.push("}".to_owned());
+        let mut args: Vec<(String, String)> = Vec::new();
         if variables_included {
-            code_lines.insert(0, format!("pub async fn {}(params: &HashMap<&str, &str>) -> Result<Response> {{", self.name));
+            args.push(("params".to_owned(), "&HashMap<&str, &str>".to_owned()));
+        }
+        if let Some(body_type) = &self.body_type {
+            args.push(("body".to_owned(), format!("&{}", body_type)));
+        }
+        if self.is_operation_name_required() {
+            args.push(("operation_name".to_owned(), "&str".to_owned()));
+        }
+        let to_declares = |args: &[(String, String)]| args.iter().map(|(name, arg_type)| format!("{}: {}", name, arg_type)).collect::<Vec<String>>().join(", ");
+        let dependencies = self.dependencies();
+        if dependencies.is_empty() {
+            code_lines.insert(0, format!("pub async fn {}({}) -> httpfile::Result<{}> {{", self.name, to_declares(&args), return_type));
+            return code_lines.join("\n");
+        }
+        let mut dependency_args = args.clone();
+        dependency_args.extend(dependencies.iter().map(|name| (format!("{}_response", name), "&httpfile::chain::CapturedResponse".to_owned())));
+        code_lines.insert(0, format!("pub async fn {}_with({}) -> httpfile::Result<{}> {{", self.name, to_declares(&dependency_args), return_type));
+        code_lines.push("".to_owned());
+        code_lines.push(format!("pub async fn {}({}) -> httpfile::Result<{}> {{", self.name, to_declares(&args), return_type));
+        code_lines.push(code_lines[1].clone());
+        for name in &dependencies {
+            // `depends-on` tags are added by Builder for dependencies taking params
+            let params_code = if self.tags.contains(&format!("depends-on {} params", name)) { "params" } else { "" };
+            code_lines.push(format!("  let {}_response = httpfile::chain::capture(location, {}({}).await?).await?;", name, name, params_code));
+        }
+        let call_args = args.iter().map(|(name, _)| name.clone()).chain(dependencies.iter().map(|name| format!("&{}_response", name))).collect::<Vec<String>>();
+        code_lines.push(format!("  {}_with({}).await", self.name, call_args.join(", ")));
+        code_lines.push("}".to_owned());
+        code_lines.join("\n")
+    }
+
+    /// http body with variables is rendered by handlebars template, and other bodies are formatted directly
+    pub fn is_body_template(&self) -> bool {
+        let is_http_request = is_http_method(&self.method) || self.method == "JSONRPC";
+        is_http_request && self.body_type.is_none() && self.body.as_ref().is_some_and(|body| body.contains("{{"))
+    }
+
+    /// handlebars template name and text, and only variables are rendered by template for GraphQL request.
+    /// response references are looked up by name because of dots in them, such as `{{lookup this "login.response.body.$.token"}}`
+    pub fn body_template(&self) -> Option<(String, String)> {
+        let to_template = |text: &str| {
+            render_variables(text, &|name: &str| match chain::parse_response_reference(name) {
+                Some(reference) => format!("{{{{lookup this {:?}}}}}", reference.variable),
+                None => format!("{{{{{}}}}}", name),
+            })
+        };
+        if self.method == "GRAPHQL" {
+            let variables = self.graphql_variables.as_deref().filter(|variables| variables.contains("{{"));
+            return variables.map(|variables| (format!("{}_variables", self.name), to_template(variables)));
+        }
+        self.body.as_deref().filter(|_| self.is_body_template()).map(|body| (format!("{}_body", self.name), to_template(body)))
+    }
+
+    /// references to responses of named requests in url, headers, body and tags, such as `{{login.response.body.$.token}}`
+    pub fn response_references(&self) -> Vec<ResponseReference> {
+        let mut texts: Vec<&str> = self.headers.iter().map(|header| header.value.as_str()).collect();
+        texts.push(&self.path);
+        texts.extend(self.uri.as_deref());
+        texts.extend(self.body.as_deref());
+        texts.extend(self.graphql_variables.as_deref());
+        texts.extend(self.tags.iter().map(|tag| tag.as_str()));
+        let mut references: Vec<ResponseReference> = Vec::new();
+        for reference in texts.into_iter().flat_map(find_variable_names).filter_map(|name| chain::parse_response_reference(&name)) {
+            if !references.contains(&reference) {
+                references.push(reference);
+            }
+        }
+        references
+    }
+
+    /// names of requests referenced by this request in order of first reference
+    pub fn dependencies(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for reference in self.response_references() {
+            if !names.contains(&reference.request_name) {
+                names.push(reference.request_name);
+            }
+        }
+        names
+    }
+
+    /// generated function takes params if variables are used in url, headers, body, topic or `@proxy` tag
+    pub fn is_params_required(&self) -> bool {
+        let mut texts: Vec<&str> = self.headers.iter().map(|header| header.value.as_str()).collect();
+        texts.extend(self.uri.as_deref());
+        texts.extend(self.get_tag_value("proxy"));
+        if self.is_broker_request() || self.is_command_request() {
+            texts.push(&self.path);
+        }
+        if self.method == "GRAPHQL" {
+            texts.extend(self.graphql_variables.as_deref());
+        } else if self.body_type.is_none() {
+            texts.extend(self.body.as_deref());
+        }
+        texts.iter().any(|text| text.contains("{{"))
+    }
+
+    /// OAuth2 token variables in url, headers and body, such as `$auth.token("auth-id")`
+    pub fn auth_variable_names(&self) -> Vec<String> {
+        let mut texts: Vec<&str> = self.headers.iter().map(|header| header.value.as_str()).collect();
+        texts.extend(self.uri.as_deref());
+        texts.extend(self.body.as_deref());
+        let mut names: Vec<String> = Vec::new();
+        for name in texts.into_iter().flat_map(find_variable_names) {
+            if name.starts_with("$auth.token(") && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// render variables, such as `{{host}}`, in request line, headers and body with values from resolver
+    pub fn render<F: Fn(&str) -> String>(&self, resolver: F) -> HttpRequest {
+        let mut request = self.clone();
+        request.request_line = render_variables(&self.request_line, &resolver);
+        request.path = render_variables(&self.path, &resolver);
+        request.uri = self.uri.as_ref().map(|uri| render_variables(uri, &resolver));
+        for header in request.headers.iter_mut() {
+            header.value = render_variables(&header.value, &resolver);
+        }
+        request.body = self.body.as_ref().map(|body| render_variables(body, &resolver));
+        // values in GraphQL variables are escaped as json text, such as `"` and new line
+        request.graphql_variables = self.graphql_variables.as_ref().map(|variables| render_variables(variables, &|name: &str| graphql::escape_json(&resolver(name))));
+        request.jsonrpc_params = self.jsonrpc_params.as_ref().map(|params| render_variables(params, &resolver));
+        if self.method == "GRAPHQL" {
+            request.body = request.to_graphql_body();
+        }
+        request.tags = self.tags.iter().map(|tag| render_variables(tag, &resolver)).collect();
+        request
+    }
+
+    pub fn to_http_code(&self) -> String {
+        let mut code_lines: Vec<String> = Vec::new();
+        match &self.comment {
+            Some(comment) if !comment.is_empty() => code_lines.push(format!("### {}", comment)),
+            _ => code_lines.push("###".to_owned()),
+        }
+        for comment in &self.comments {
+            code_lines.push(format!("// {}", comment).trim_end().to_string());
+        }
+        for tag in &self.tags {
+            code_lines.push(format!("//@{}", tag));
+        }
+        if let Some(protocol) = self.protocol.as_ref().or(self.jsonrpc_method.as_ref()) {
+            code_lines.push(format!("{} {} {}", self.method, self.path, protocol));
         } else {
-            code_lines.insert(0, format!("pub async fn {}() -> Result<Response> {{", self.name));
+            code_lines.push(format!("{} {}", self.method, self.path));
+        }
+        for (i, header) in self.headers.iter().enumerate() {
+            push_header_comments(&mut code_lines, &self.header_comments, i);
+            code_lines.push(format!("{}: {}", to_canonical_header_name(&header.name), header.value));
+        }
+        push_header_comments(&mut code_lines, &self.header_comments, self.headers.len());
+        // body
+        let mut body_blocks: Vec<String> = Vec::new();
+        if self.method == "GRAPHQL" {
+            if let Some(query) = &self.graphql_query {
+                body_blocks.push(query.clone());
+            }
+            if let Some(variables) = &self.graphql_variables {
+                body_blocks.push(to_pretty_json(variables));
+            }
+        } else if self.method == "JSONRPC" {
+            if let Some(params) = &self.jsonrpc_params {
+                body_blocks.push(params.clone());
+            }
+        } else if let Some(body) = &self.body {
+            body_blocks.push(to_pretty_json(body));
+        }
+        // response handlers and references
+        let mut handler_lines: Vec<String> = Vec::new();
+        if let Some(js_code) = &self.js_code {
+            handler_lines.push("> {%".to_owned());
+            handler_lines.push(js_code.clone());
+            handler_lines.push("%}".to_owned());
+        }
+        if let Some(js_file) = &self.js_file {
+            handler_lines.push(format!("> {}", js_file));
+        }
+        if let Some(redirect_response) = &self.redirect_response {
+            handler_lines.push(redirect_response.clone());
+        }
+        for assertion in &self.assertions {
+            handler_lines.push(format!("> assert {}", assertion.expression));
+        }
+        for response_ref in &self.response_refs {
+            handler_lines.push(format!("<> {}", response_ref));
+        }
+        if !handler_lines.is_empty() {
+            body_blocks.push(handler_lines.join("\n"));
+        }
+        for block in body_blocks {
+            code_lines.push("".to_owned());
+            code_lines.push(block);
         }
         code_lines.join("\n")
     }
 }
 
+impl PartialEq for HttpRequest {
+    /// requests are equal when they describe the same call, and positions in the http file are ignored
+    fn eq(&self, other: &Self) -> bool {
+        self.comment == other.comment
+            && self.comments == other.comments
+            && self.header_comments == other.header_comments
+            && self.tags == other.tags
+            && self.name == other.name
+            && self.method == other.method
+            && self.path == other.path
+            && self.protocol == other.protocol
+            && self.jsonrpc_method == other.jsonrpc_method
+            && self.uri == other.uri
+            && self.headers == other.headers
+            && is_same_body(&self.body, &other.body)
+            && self.js_code == other.js_code
+            && self.js_file == other.js_file
+            && self.redirect_response == other.redirect_response
+            && self.response_refs == other.response_refs
+            && self.assertions == other.assertions
+            && self.graphql_query == other.graphql_query
+            && is_same_body(&self.graphql_variables, &other.graphql_variables)
+    }
+}
+
+fn push_header_comments(code_lines: &mut Vec<String>, header_comments: &[(usize, String)], position: usize) {
+    for (_, comment) in header_comments.iter().filter(|(offset, _)| *offset == position) {
+        code_lines.push(format!("// {}", comment).trim_end().to_string());
+    }
+}
+
+/// canonical header name, such as `content-type` to `Content-Type`
+pub fn to_canonical_header_name(name: &str) -> String {
+    name.split('-')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+/// kebab case name for request and environment, such as `list-pets` for `listPets`
+pub fn to_kebab_case(text: &str) -> String {
+    let mut name = String::new();
+    let mut previous_lowercase = false;
+    for c in text.chars() {
+        if c.is_ascii_uppercase() && previous_lowercase {
+            name.push('-');
+        }
+        previous_lowercase = c.is_ascii_lowercase() || c.is_ascii_digit();
+        name.push(if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' });
+    }
+    name.split('-').filter(|part| !part.is_empty()).collect::<Vec<&str>>().join("-")
+}
+
+/// unique request name in names, such as `get-ip-2` if `get-ip` exists
+pub fn to_unique_name(name: &str, names: &[String]) -> String {
+    let mut unique_name = name.to_string();
+    let mut index = 1;
+    while names.contains(&unique_name.replace('-', "_")) {
+        index += 1;
+        unique_name = format!("{}-{}", name, index);
+    }
+    unique_name
+}
+
+/// JSON-RPC 2.0 request object, and params are omitted if empty
+fn to_jsonrpc_envelope(id: u64, method: &str, params: &str) -> String {
+    let method = serde_json::to_string(method).unwrap();
+    if params.trim().is_empty() {
+        format!(r#"{{"jsonrpc": "2.0", "id": {}, "method": {}}}"#, id, method)
+    } else {
+        format!(r#"{{"jsonrpc": "2.0", "id": {}, "method": {}, "params": {}}}"#, id, method, params.trim())
+    }
+}
+
+/// pretty print json text with fields order kept, and text is returned as it is if not json
+pub fn to_pretty_json(text: &str) -> String {
+    match serde_json::from_str::<serde_json::Value>(text) {
+        Ok(json) if json.is_object() || json.is_array() => serde_json::to_string_pretty(&json).unwrap(),
+        _ => text.to_string(),
+    }
+}
+
+fn is_same_body(body: &Option<String>, other: &Option<String>) -> bool {
+    match (body, other) {
+        (Some(body), Some(other)) => {
+            if body == other {
+                return true;
+            }
+            match (serde_json::from_str::<serde_json::Value>(body), serde_json::from_str::<serde_json::Value>(other)) {
+                (Ok(json), Ok(other_json)) => json == other_json,
+                _ => false,
+            }
+        }
+        (None, None) => true,
+        _ => false,
+    }
+}
+
 pub fn is_legal_method(method: &str) -> bool {
     matches!(method,
-        "GET" | "POST" | "PUT" | "DELETE"| "GRAPHQL"
+        "GET" | "POST" | "PUT" | "DELETE" | "PATCH" | "HEAD" | "OPTIONS" | "TRACE" | "GRAPHQL" | "WEBSOCKET" | "GRPC" | "JSONRPC" | "PUB" | "SUB" | "SHELL" | "SSH"
     )
 }
 
@@ -236,45 +1006,86 @@ pub fn is_http_method(method: &str) -> bool {
     )
 }
 
-fn replace_variables(text: &str) -> (String, Vec<String>) {
+/// replace `{{name}}` variables in text with values from resolver, and resolver receives trimmed name, such as `$uuid`
+pub fn render_variables<F: Fn(&str) -> String>(text: &str, resolver: &F) -> String {
     let mut result = String::new();
-    let mut variable_names: Vec<String> = Vec::new();
-    let mut offset = text.find("{{").unwrap();
-    let code_length = text.len();
-    result.push_str(&text[..offset]);
-    offset += 2;
-    while offset < code_length {
-        let temp_offset = text[offset..].find("}}");
-        if temp_offset.is_none() {
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let name_offset = start + 2;
+        if let Some(end) = rest[name_offset..].find("}}") {
+            result.push_str(&rest[..start]);
+            result.push_str(&resolver(rest[name_offset..(name_offset + end)].trim()));
+            rest = &rest[(name_offset + end + 2)..];
+        } else {
             break;
         }
-        let mut name = text[offset..(offset + temp_offset.unwrap())]
-            .trim()
-            .to_string();
-        if name.starts_with('$') {
-            name = name[1..].to_string();
-        }
-        name = name.replace("-", "_");
-        variable_names.push(name.clone());
-        result.push_str(&format!("{{{}}}", name));
-        offset += temp_offset.unwrap() + 2;
-        if let Some(value) = text[offset..].find("{{") {
-            result.push_str(text[offset..(offset + value)].to_string().as_str());
-            offset += value + 2;
+    }
+    result.push_str(rest);
+    result
+}
+
+/// replace variables in text with format arguments, and format text with (argument, variable name) pairs are returned,
+/// such as `https://{host}/ip` with `("host", "host")` for `https://{{host}}/ip`
+fn replace_variables(text: &str) -> (String, Vec<(String, String)>) {
+    let mut result = String::new();
+    let mut variables: Vec<(String, String)> = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let name_offset = start + 2;
+        if let Some(end) = rest[name_offset..].find("}}") {
+            result.push_str(&rest[..start].replace('{', "{{").replace('}', "}}"));
+            let name = rest[name_offset..(name_offset + end)].trim().to_string();
+            // variables are deduplicated by name, and names with same argument, such as `a-b` and `a_b`, get suffix
+            let argument = match variables.iter().find(|(_, other)| other == &name) {
+                Some((argument, _)) => argument.clone(),
+                None => {
+                    let base: String = name.trim_start_matches('$').chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+                    let mut argument = base.clone();
+                    let mut index = 1;
+                    while variables.iter().any(|(other, _)| other == &argument) {
+                        index += 1;
+                        argument = format!("{}_{}", base, index);
+                    }
+                    variables.push((argument.clone(), name));
+                    argument
+                }
+            };
+            result.push_str(&format!("{{{}}}", argument));
+            rest = &rest[(name_offset + end + 2)..];
         } else {
-            result.push_str(text[offset..].to_string().as_str());
             break;
         }
     }
-    (result, variable_names)
+    result.push_str(&rest.replace('{', "{{").replace('}', "}}"));
+    (result, variables)
 }
 
+/// rust type for `@response-type`: `text` as `String`, `bytes` as `Vec<u8>`, `json` as `serde_json::Value`,
+/// and other value is rust type decoded from json, such as `crate::MyIp`
+pub fn to_response_rust_type(response_type: &str) -> &str {
+    match response_type {
+        "text" | "String" => "String",
+        "bytes" | "Vec<u8>" => "Vec<u8>",
+        "json" => "serde_json::Value",
+        rust_type => rust_type,
+    }
+}
+
+/// format code for text with variables, and params are read with `httpfile::get_param`
 pub fn to_place_holder(text: &str) -> String {
-    let result = replace_variables(text);
-    let params_declare = result.1.into_iter().map(|name| format!(r#"{} = params.get("{}").unwrap_or(&"")"#, name, name)).collect::<Vec<String>>().join(", ");
-    format!("format!(r#\"{}\"#, {})", result.0, params_declare)
+    let (format_text, variables) = replace_variables(text);
+    let params_declare = variables
+        .into_iter()
+        .map(|(argument, name)| format!("{} = httpfile::get_param(location, params, {:?})?", argument, name))
+        .collect::<Vec<String>>()
+        .join(", ");
+    format!("format!(r#\"{}\"#, {})", format_text, params_declare)
 }
 
+/// variable names in text, such as `host` and `$uuid`
+pub fn find_variable_names(text: &str) -> Vec<String> {
+    replace_variables(text).1.into_iter().map(|(_, name)| name).collect()
+}
 
 #[cfg(test)]
 mod tests {
@@ -291,6 +1102,17 @@ mod tests {
     }
 
 
+    #[test]
+    fn test_to_place_holder() {
+        let code = to_place_holder("https://{{host}}/{{a-b}}/{{a_b}}/{{a-b}}");
+        println!("{}", code);
+        assert_eq!(
+            code,
+            r##"format!(r#"https://{host}/{a_b}/{a_b_2}/{a_b}"#, host = httpfile::get_param(location, params, "host")?, a_b = httpfile::get_param(location, params, "a-b")?, a_b_2 = httpfile::get_param(location, params, "a_b")?)"##
+        );
+        assert_eq!(find_variable_names("{{a-b}}{{a_b}}{{a-b}}"), vec!["a-b", "a_b"]);
+    }
+
     #[test]
     fn test_to_rust_code() {
         let http_code = r#"
@@ -332,7 +1154,450 @@ query {
         let mut requests = parse_http_code(http_code);
         let request = requests.remove(0);
         println!("{:?}", request);
-        println!("{}", request.to_rust_code());
+        let rust_code = request.to_rust_code();
+        println!("{}", rust_code);
+        assert_eq!(request.graphql_query.as_deref(), Some("query {\nip\n}"));
+        assert!(rust_code.contains(r#"let variables = httpfile::render_template(location, &GRAPHQL_HANDLEBARS, "graphql_demo_variables", params, &["nick"])?;"#));
+        // values of variables are escaped as json text by `httpfile run`
+        let nick = "a\"b \\ O'Brien\nnext";
+        let rendered = request.render(|name| if name == "nick" { nick.to_owned() } else { format!("{{{{{}}}}}", name) });
+        let body: serde_json::Value = serde_json::from_str(rendered.body.as_deref().unwrap()).unwrap();
+        assert_eq!(body["variables"]["name"], nick);
+        assert!(rust_code.contains(r#"let body = httpfile::graphql::to_body(location, "query {\nip\n}", None, Some(&variables))?;"#));
+    }
+
+    #[test]
+    fn test_graphql_operations_to_rust_code() {
+        let http_code = r#"
+### GraphQL operations
+//@name user-operations
+GRAPHQL https://example.com/graphql
+
+query GetUser($id: ID!) { user(id: $id) { name, bio } }
+mutation Logout { logout }
+
+{"id": "1\t2"}
+
+### GraphQL subscription
+//@name on-message
+//@operation-name OnMessage
+GRAPHQL https://example.com/graphql
+
+subscription OnMessage { message { text } }
+"#;
+        let requests = parse_http_code(http_code);
+        assert_eq!(requests[0].graphql_variables.as_deref(), Some(r#"{"id": "1\t2"}"#));
+        let body: serde_json::Value = serde_json::from_str(requests[0].body.as_deref().unwrap()).unwrap();
+        assert_eq!(body["variables"]["id"], "1\t2");
+        let rust_code = requests[0].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.starts_with("pub async fn user_operations(operation_name: &str) -> httpfile::Result<Response> {"));
+        assert!(rust_code.contains("Some(operation_name), Some("));
+        assert!(requests[1].body.as_deref().unwrap().contains(r#""operationName":"OnMessage""#));
+        let rust_code = requests[1].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.starts_with("pub async fn on_message() -> httpfile::Result<httpfile::graphql::SubscriptionStream> {"));
+        assert!(rust_code.contains("  httpfile::graphql::subscribe(location, &url, headers, &body).await"));
+    }
+
+    #[test]
+    fn test_response_type_to_rust_code() {
+        let http_code = r#"
+### get my ip
+//@name my-ip
+//@response-type crate::MyIp
+GET https://httpbin.org/ip
+"#;
+        let requests = parse_http_code(http_code);
+        let rust_code = requests[0].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.starts_with("pub async fn my_ip() -> httpfile::Result<crate::MyIp> {"));
+        assert!(rust_code.contains(r#"let location = &httpfile::RequestLocation::new("my-ip", HTTP_FILE, 5);"#));
+        assert!(rust_code.contains("httpfile::decode_json::<crate::MyIp>(location, response).await"));
+    }
+
+    #[test]
+    fn test_expect_status_to_rust_code() {
+        let http_code = r#"
+### post test
+//@name post-test
+//@expect-status 200, 201
+POST https://httpbin.org/post
+"#;
+        let requests = parse_http_code(http_code);
+        let rust_code = requests[0].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.contains(r#"  let response = httpfile::expect_status(location, response, &["200", "201"]).await?;
+  Ok(response)
+}"#));
+    }
+
+    #[test]
+    fn test_digest_auth_to_rust_code() {
+        let http_code = r#"
+### digest auth
+GET https://httpbin.org/digest-auth/auth/user/passwd
+Authorization: Digest {{user}} passwd
+"#;
+        let requests = parse_http_code(http_code);
+        let rust_code = requests[0].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.contains(r##"let digest_auth = format!(r#"Digest {user} passwd"#"##));
+        assert!(rust_code.contains("httpfile::send_with_digest_auth(location, CLIENT.get(url).headers(headers), &digest_auth).await"));
+        assert!(!rust_code.contains(r#"insert_header(location, &mut headers, "Authorization""#));
+    }
+
+    #[test]
+    fn test_auth_token_to_rust_code() {
+        let http_code = r#"
+### oauth2 token
+GET https://httpbin.org/bearer
+Authorization: Bearer {{$auth.token("auth-id")}}
+"#;
+        let requests = parse_http_code(http_code);
+        let rust_code = requests[0].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.contains(r#"let auth_tokens = httpfile::oauth2::fetch_tokens(location, &["$auth.token(\"auth-id\")"]).await?;"#));
+        assert!(rust_code.contains(r#"auth_token__auth_id__ = httpfile::get_param(location, params, "$auth.token(\"auth-id\")")?"#));
+    }
+
+    #[test]
+    fn test_insecure_to_rust_code() {
+        let http_code = r#"
+### self signed
+//@insecure
+GET https://self-signed.badssl.com/
+"#;
+        let requests = parse_http_code(http_code);
+        let rust_code = requests[0].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.contains("httpfile::send(location, INSECURE_CLIENT.get(url).headers(headers)).await"));
+        assert!(rust_code.contains("  let headers = HeaderMap::new();"));
+    }
+
+    #[test]
+    fn test_proxy_to_rust_code() {
+        let http_code = r#"
+### via proxy
+//@proxy socks5://{{proxy-host}}:1080
+//@insecure
+GET https://httpbin.org/ip
+"#;
+        let requests = parse_http_code(http_code);
+        let rust_code = requests[0].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.contains(r##"let client = httpfile::proxy::client(location, &format!(r#"socks5://{proxy_host}:1080"#, proxy_host = httpfile::get_param(location, params, "proxy-host")?), true, &ROOT_CERTIFICATES)?;"##));
+        assert!(rust_code.contains("httpfile::send(location, client.get(url).headers(headers)).await"));
+    }
+
+    #[test]
+    fn test_websocket_to_rust_code() {
+        let http_code = r#"
+### chat
+//@name chat
+WEBSOCKET ws://{{host}}/ws
+Content-Type: application/json
+
+===
+{"message": "Hello, {{nick}}"}
+=== wait-for-server
+{"message": "Bye"}
+"#;
+        let requests = parse_http_code(http_code);
+        assert_eq!(requests[0].method, "WEBSOCKET");
+        assert_eq!(requests[0].uri.as_deref(), Some("ws://{{host}}/ws"));
+        assert!(!requests[0].is_body_template());
+        let rust_code = requests[0].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.starts_with("pub async fn chat(params: &HashMap<&str, &str>) -> httpfile::Result<httpfile::websocket::MessageStream> {"));
+        assert!(rust_code.contains(r#"nick = httpfile::get_param(location, params, "nick")?"#));
+        assert!(rust_code.contains("  httpfile::websocket::connect(location, &url, headers, httpfile::websocket::parse_messages(&body)).await"));
+    }
+
+    #[test]
+    fn test_sse_to_rust_code() {
+        let http_code = r#"
+### job progress
+//@name job-progress
+//@sse
+GET https://{{host}}/jobs/1/events
+"#;
+        let requests = parse_http_code(http_code);
+        let rust_code = requests[0].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.starts_with("pub async fn job_progress(params: &HashMap<&str, &str>) -> httpfile::Result<httpfile::sse::EventStream> {"));
+        assert!(rust_code.contains("  httpfile::sse::connect(location, CLIENT.get(url).headers(headers)).await"));
+    }
+
+    #[test]
+    fn test_grpc_to_rust_code() {
+        let http_code = r#"
+### say hello
+//@name say-hello
+//@descriptor /tmp/greeter.desc
+GRPC localhost:50051/helloworld.Greeter/SayHello
+x-token: {{token}}
+
+{
+  "name": "Rust"
+}
+"#;
+        let requests = parse_http_code(http_code);
+        let rust_code = requests[0].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.starts_with("pub async fn say_hello(params: &HashMap<&str, &str>) -> httpfile::Result<serde_json::Value> {"));
+        assert!(rust_code.contains(r#"  let url = "http://localhost:50051/helloworld.Greeter/SayHello";"#));
+        assert!(rust_code.contains(r#"httpfile::grpc::call::<serde_json::Value>(location, &url, headers, &body, Some(include_bytes!("/tmp/greeter.desc").as_slice())).await"#));
+    }
+
+    #[test]
+    fn test_grpc_response_type() {
+        let http_code = r#"
+### say hello
+//@name say-hello
+//@response-type text
+GRPC localhost:50051/helloworld.Greeter/SayHello
+
+### say hello again
+//@name say-hello-again
+//@response-type crate::HelloReply
+GRPC localhost:50051/helloworld.Greeter/SayHelloAgain
+"#;
+        let requests = parse_http_code(http_code);
+        let error = requests[0].validate_response_type().unwrap_err();
+        println!("{}", error);
+        assert!(error.contains("`@response-type text` is not supported for GRPC request `say_hello`"));
+        assert!(requests[1].validate_response_type().is_ok());
+        assert!(requests[1].to_rust_code().contains("httpfile::grpc::call::<crate::HelloReply>(location, &url, headers, \"{}\", None).await"));
+    }
+
+    #[test]
+    fn test_grpc_assertions_to_rust_code() {
+        let http_code = r#"
+### say hello
+//@name say-hello
+//@response-type crate::HelloReply
+GRPC localhost:50051/helloworld.Greeter/SayHello
+
+{"name": "Rust"}
+> assert $.message contains Rust
+"#;
+        let requests = parse_http_code(http_code);
+        requests[0].validate_assertions().unwrap();
+        let rust_code = requests[0].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.starts_with("pub async fn say_hello() -> httpfile::Result<crate::HelloReply> {"));
+        assert!(rust_code.contains("  let json = httpfile::grpc::call::<serde_json::Value>(location, &url, headers, &body, None).await?;"));
+        assert!(rust_code.contains(r#"  httpfile::assertion::check(location, &httpfile::chain::CapturedResponse::from(json.clone()), &[httpfile::assertion::Assertion::new(8, "$.message", "contains", "Rust")])?;"#));
+        assert!(rust_code.contains("  httpfile::grpc::decode(location, json)"));
+        // generated functions of streaming requests return streams, so assertions are rejected
+        let requests = parse_http_code("WEBSOCKET ws://localhost:8080/chat\n\n> assert body contains hello\n");
+        assert_eq!(requests[0].validate_assertions().unwrap_err(), format!("line 3: assertions are not supported for WEBSOCKET request `{}`", requests[0].name));
+    }
+
+    #[test]
+    fn test_jsonrpc_to_rust_code() {
+        let http_code = r#"
+### get balance
+//@name get-balance
+JSONRPC https://{{host}}/rpc eth_getBalance
+content-type: application/json-rpc
+
+["{{address}}", "latest"]
+
+### batch calls
+//@name batch-calls
+//@jsonrpc-id 10
+JSONRPC https://{{host}}/rpc
+
+=== eth_blockNumber
+=== eth_getBalance
+["0x407d73d8a49eeb85d32cf465507dd71d507100c1", "latest"]
+
+### block number
+//@name block-number
+//@response-type text
+JSONRPC https://{{host}}/rpc eth_blockNumber
+"#;
+        let requests = parse_http_code(http_code);
+        assert_eq!(requests[0].jsonrpc_method.as_deref(), Some("eth_getBalance"));
+        assert_eq!(requests[0].body.as_deref(), Some(r#"{"jsonrpc": "2.0", "id": 1, "method": "eth_getBalance", "params": ["{{address}}", "latest"]}"#));
+        let rust_code = requests[0].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.starts_with("pub async fn get_balance(params: &HashMap<&str, &str>) -> httpfile::Result<serde_json::Value> {"));
+        assert!(rust_code.contains("CLIENT.post(url).headers(headers).body(body)"));
+        assert!(rust_code.contains("httpfile::jsonrpc::decode_result::<serde_json::Value>(location, response).await"));
+        // Content-Type header is replaced with `application/json` whatever case of header name
+        assert!(!rust_code.contains("application/json-rpc"));
+        assert!(rust_code.contains(r#"httpfile::insert_header(location, &mut headers, "Content-Type", "application/json")?;"#));
+        assert!(requests[1].is_jsonrpc_batch());
+        let body = requests[1].body.as_deref().unwrap();
+        assert!(body.contains(r#"{"jsonrpc": "2.0", "id": 10, "method": "eth_blockNumber"},"#));
+        assert!(body.contains(r#"{"jsonrpc": "2.0", "id": 11, "method": "eth_getBalance", "params": ["#));
+        assert!(serde_json::from_str::<serde_json::Value>(body).is_ok());
+        let rust_code = requests[1].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.contains("-> httpfile::Result<Vec<httpfile::jsonrpc::RpcResult>> {"));
+        assert_eq!(parse_http_code(&requests[1].to_http_code())[0], requests[1]);
+        let rust_code = requests[2].to_rust_code();
+        assert!(rust_code.contains("-> httpfile::Result<String> {"));
+        assert!(rust_code.contains("httpfile::jsonrpc::decode_result::<String>(location, response).await"));
+    }
+
+    #[test]
+    fn test_broker_to_rust_code() {
+        let http_code = r#"
+### publish order
+//@name publish-order
+PUB orders/{{region}}
+URI: mqtt://{{mqtt-host}}:1883
+Content-Type: application/json
+
+{
+  "id": 1
+}
+
+### subscribe orders
+//@name subscribe-orders
+SUB orders
+URI: nats://localhost:4222
+"#;
+        let requests = parse_http_code(http_code);
+        assert_eq!(requests[0].uri.as_deref(), Some("mqtt://{{mqtt-host}}:1883"));
+        let rust_code = requests[0].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.starts_with("pub async fn publish_order(params: &HashMap<&str, &str>) -> httpfile::Result<()> {"));
+        assert!(!rust_code.contains(r#""URI""#));
+        assert!(rust_code.contains(r##"httpfile::broker::publish(location, &url, &format!(r#"orders/{region}"#, region = httpfile::get_param(location, params, "region")?), headers, &body).await"##));
+        let rust_code = requests[1].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.starts_with("pub async fn subscribe_orders() -> httpfile::Result<httpfile::broker::MessageStream> {"));
+        assert!(rust_code.contains(r#"  httpfile::broker::subscribe(location, &url, "orders").await"#));
+        assert!(!rust_code.contains("headers"));
+    }
+
+    #[test]
+    fn test_assertions_to_rust_code() {
+        let http_code = r#"
+### list items
+//@name list-items
+GET https://{{host}}/items
+
+> assert status == 200
+> assert $.items.length > 0
+> assert $.items[?@.price < 10].name exists
+> assert header Content-Type contains "json"
+
+### disk usage
+//@name disk-usage
+SHELL sh
+
+df -h
+> assert status == 0
+> assert body contains /
+"#;
+        let requests = parse_http_code(http_code);
+        assert_eq!(requests[0].assertions.len(), 4);
+        assert_eq!(requests[0].assertions[2].parse().unwrap(), ("$.items[?@.price < 10].name".to_string(), "exists".to_string(), "".to_string()));
+        assert_eq!(requests[0].assertions[3].parse().unwrap().2, "json");
+        requests[0].validate_assertions().unwrap();
+        let rust_code = requests[0].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.contains(r#"httpfile::assertion::Assertion::new(6, "status", "==", "200")"#));
+        assert!(rust_code.contains("  let response = httpfile::assertion::check_response(location, response, &[httpfile::assertion::Assertion::new(6, "));
+        assert!(rust_code.contains("  Ok(response)"));
+        let rust_code = requests[1].to_rust_code();
+        println!("{}", rust_code);
+        assert_eq!(requests[1].body.as_deref(), Some("df -h"));
+        assert!(rust_code.contains("  httpfile::assertion::check(location, &httpfile::chain::CapturedResponse::from(output.clone()), &["));
+        assert!(rust_code.contains("  Ok(output)"));
+        let illegal = HttpAssertion::new(3, "cookie id == 1".to_string());
+        assert_eq!(illegal.parse().unwrap_err(), "line 3: unknown assertion subject `cookie id`");
+        assert!(HttpAssertion::new(4, "status 200".to_string()).parse().is_err());
+        assert!(HttpAssertion::new(5, "body exists yes".to_string()).parse().is_err());
+    }
+
+    #[test]
+    fn test_command_to_rust_code() {
+        let http_code = r#"
+### seed users
+//@name seed-users
+//@insecure
+SSH {{ssh-user}}@{{ssh-host}}:2222
+Password: {{ssh-password}}
+
+psql -c "insert into users(name) values ('linux_china')"
+
+### list files
+//@name list-files
+//@response-type text
+SHELL bash
+X-Dir: {{dir}}
+
+ls $X_DIR
+
+### backup
+//@name backup
+//@response-type bytes
+SSH root@db.example.com
+
+pg_dump users
+"#;
+        let requests = parse_http_code(http_code);
+        assert!(requests[0].is_command_request());
+        assert_eq!(requests[0].path, "{{ssh-user}}@{{ssh-host}}:2222");
+        let rust_code = requests[0].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.starts_with("pub async fn seed_users(params: &HashMap<&str, &str>) -> httpfile::Result<httpfile::shell::CommandOutput> {"));
+        assert!(rust_code.contains(r##"httpfile::insert_header(location, &mut headers, "Password", &format!(r#"{ssh_password}"#"##));
+        assert!(rust_code.contains("  httpfile::shell::ssh(location, &format!("));
+        assert!(rust_code.contains(", true, &headers, &body).await"));
+        let rust_code = requests[1].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.starts_with("pub async fn list_files(params: &HashMap<&str, &str>) -> httpfile::Result<String> {"));
+        assert!(rust_code.contains(r#"  let output = httpfile::shell::run(location, "bash", &headers, &body).await?;"#));
+        assert!(rust_code.contains("  Ok(output.text().to_owned())"));
+        // unknown host keys are rejected without `@insecure` tag
+        let rust_code = requests[2].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.contains(r#"  let output = httpfile::shell::ssh(location, "root@db.example.com", false, &headers, &body).await?;"#));
+        assert!(rust_code.starts_with("pub async fn backup() -> httpfile::Result<Vec<u8>> {"));
+        assert!(rust_code.contains("  Ok(output.stdout.into_bytes())"));
+    }
+
+    #[test]
+    fn test_chained_to_rust_code() {
+        let http_code = r#"
+### login
+//@name login
+POST https://{{host}}/login
+
+### profile
+//@name profile
+//@depends-on login params
+POST https://{{host}}/users/{{login.response.body.$.id}}
+Authorization: Bearer {{login.response.body.$.token}}
+
+{"location": "{{login.response.headers.Location}}"}
+"#;
+        let requests = parse_http_code(http_code);
+        assert_eq!(requests[1].dependencies(), vec!["login".to_owned()]);
+        assert!(requests[0].is_params_required());
+        let rust_code = requests[1].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.starts_with("pub async fn profile_with(params: &HashMap<&str, &str>, login_response: &httpfile::chain::CapturedResponse) -> httpfile::Result<Response> {"));
+        assert!(rust_code.contains(r#"  let references = httpfile::chain::resolve(location, &[("login.response.body.$.token", login_response), ("#));
+        assert!(rust_code.contains(r#"login_response_body___token = httpfile::get_param(location, params, "login.response.body.$.token")?"#));
+        assert!(rust_code.contains("pub async fn profile(params: &HashMap<&str, &str>) -> httpfile::Result<Response> {"));
+        assert!(rust_code.contains("  let login_response = httpfile::chain::capture(location, login(params).await?).await?;"));
+        assert!(rust_code.contains("  profile_with(params, &login_response).await"));
+        let (template_name, template_text) = requests[1].body_template().unwrap();
+        assert_eq!(template_name, "profile_body");
+        assert_eq!(template_text, r#"{"location": "{{lookup this "login.response.headers.Location"}}"}"#);
+        let mut handlebars = Handlebars::new();
+        handlebars.register_template_string(&template_name, template_text).unwrap();
+        let data = HashMap::from([("login.response.headers.Location", "/users/1")]);
+        assert_eq!(handlebars.render(&template_name, &data).unwrap(), r#"{"location": "/users/1"}"#);
     }
 
     #[test]
@@ -349,6 +1614,13 @@ hi {{name}}, nice to meet you! {{$uuid}}
         println!("{:?}", result);
     }
 
+    #[test]
+    fn test_render_variables() {
+        let text = "https://{{host}}/post?id={{ $uuid }}&name={{name";
+        let result = render_variables(text, &|name: &str| name.to_uppercase());
+        assert_eq!(result, "https://HOST/post?id=$UUID&name={{name");
+    }
+
     #[test]
     fn test_handlerbars() {
         let mut params: HashMap<&str, &str> = HashMap::new();
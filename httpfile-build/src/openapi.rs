@@ -0,0 +1,485 @@
+use serde_json::{json, Map, Value};
+use crate::formatter::format_http_code;
+use crate::models::{find_variable_names, is_http_method, to_kebab_case, HttpRequest};
+
+const OPERATION_METHODS: [&str; 8] = ["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+/// generate OpenAPI 3.1 document from requests, and requests are grouped by path template
+pub fn to_openapi(requests: &[HttpRequest], title: &str) -> Value {
+    let mut servers: Vec<Value> = Vec::new();
+    let mut paths = Map::new();
+    for request in requests {
+        let json_post = request.method == "GRAPHQL" || request.method == "JSONRPC";
+        let method = if json_post { "post".to_owned() } else { request.method.to_lowercase() };
+        if !json_post && !is_http_method(&request.method) {
+            continue;
+        }
+        let url = request.uri.as_ref().unwrap_or(&request.path);
+        let (server_url, path, query) = split_url(url);
+        if let Some(server_url) = server_url {
+            let server = to_server(&server_url);
+            if !servers.contains(&server) {
+                servers.push(server);
+            }
+        }
+        let path_template = to_path_template(&path);
+        let path_item = paths
+            .entry(path_template)
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+            .unwrap();
+        if path_item.contains_key(&method) {
+            continue;
+        }
+        path_item.insert(method, to_operation(request, &path, query.as_deref()));
+    }
+    let mut doc = Map::new();
+    doc.insert("openapi".to_owned(), json!("3.1.0"));
+    doc.insert("info".to_owned(), json!({"title": title, "version": "1.0.0"}));
+    if !servers.is_empty() {
+        doc.insert("servers".to_owned(), Value::Array(servers));
+    }
+    doc.insert("paths".to_owned(), Value::Object(paths));
+    Value::Object(doc)
+}
+
+pub fn to_openapi_json(requests: &[HttpRequest], title: &str) -> String {
+    serde_json::to_string_pretty(&to_openapi(requests, title)).unwrap()
+}
+
+pub fn to_openapi_yaml(requests: &[HttpRequest], title: &str) -> String {
+    serde_yaml::to_string(&to_openapi(requests, title)).unwrap()
+}
+
+fn to_operation(request: &HttpRequest, path: &str, query: Option<&str>) -> Value {
+    let mut operation = Map::new();
+    let operation_id = request.get_tag_value("name").unwrap_or(&request.name);
+    operation.insert("operationId".to_owned(), json!(operation_id));
+    if let Some(comment) = &request.comment {
+        operation.insert("summary".to_owned(), json!(comment));
+    }
+    let mut parameters: Vec<Value> = Vec::new();
+    for name in find_variable_names(path) {
+        parameters.push(json!({"name": name, "in": "path", "required": true, "schema": {"type": "string"}}));
+    }
+    if let Some(query) = query {
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let mut parameter = json!({"name": name, "in": "query", "schema": {"type": "string"}});
+            if value.contains("{{") {
+                parameter["required"] = json!(true);
+            } else if !value.is_empty() {
+                parameter["example"] = json!(value);
+            }
+            parameters.push(parameter);
+        }
+    }
+    for header in &request.headers {
+        // Accept, Content-Type and Authorization headers are described by OpenAPI itself
+        let ignored = ["Host", "Accept", "Content-Type", "Authorization"].iter().any(|name| header.name.eq_ignore_ascii_case(name));
+        if !ignored && header.value.contains("{{") {
+            parameters.push(json!({"name": header.name, "in": "header", "required": true, "schema": {"type": "string"}}));
+        }
+    }
+    if !parameters.is_empty() {
+        operation.insert("parameters".to_owned(), Value::Array(parameters));
+    }
+    if let Some(body) = &request.body {
+        let content_type = if request.method == "GRAPHQL" || request.method == "JSONRPC" {
+            "application/json"
+        } else {
+            request.get_header_value("Content-Type").unwrap_or("application/octet-stream")
+        };
+        let content_type = content_type.split(';').next().unwrap_or_default().trim();
+        let schema = match serde_json::from_str::<Value>(body) {
+            Ok(json) => to_json_schema(&json),
+            Err(_) => json!({"type": "string"}),
+        };
+        operation.insert("requestBody".to_owned(), json!({"content": {content_type: {"schema": schema}}}));
+    }
+    operation.insert("responses".to_owned(), json!({"200": {"description": "OK"}}));
+    Value::Object(operation)
+}
+
+/// infer JSON schema from json value, and value with `{{variable}}` is taken as string
+pub fn to_json_schema(json: &Value) -> Value {
+    match json {
+        Value::Null => json!({}),
+        Value::Bool(_) => json!({"type": "boolean"}),
+        Value::Number(number) => {
+            if number.is_f64() {
+                json!({"type": "number", "example": json})
+            } else {
+                json!({"type": "integer", "example": json})
+            }
+        }
+        Value::String(text) => {
+            if text.contains("{{") {
+                json!({"type": "string"})
+            } else {
+                json!({"type": "string", "example": text})
+            }
+        }
+        Value::Array(items) => match items.first() {
+            Some(item) => json!({"type": "array", "items": to_json_schema(item)}),
+            None => json!({"type": "array"}),
+        },
+        Value::Object(fields) => {
+            let mut properties = Map::new();
+            for (name, value) in fields {
+                properties.insert(name.clone(), to_json_schema(value));
+            }
+            json!({"type": "object", "properties": properties})
+        }
+    }
+}
+
+/// split url into server url, path and query, such as `https://{{host}}`, `/users/{{id}}` and `page=1`
+fn split_url(url: &str) -> (Option<String>, String, Option<String>) {
+    let url = url.split('#').next().unwrap_or_default();
+    let (url, query) = match url.split_once('?') {
+        Some((url, query)) => (url, Some(query.to_string())),
+        None => (url, None),
+    };
+    if let Some(scheme_offset) = url.find("://") {
+        let host_offset = scheme_offset + 3;
+        match url[host_offset..].find('/') {
+            Some(path_offset) => {
+                let (server_url, path) = url.split_at(host_offset + path_offset);
+                (Some(server_url.to_string()), path.to_string(), query)
+            }
+            None => (Some(url.to_string()), "/".to_owned(), query),
+        }
+    } else {
+        (None, url.to_string(), query)
+    }
+}
+
+fn to_server(server_url: &str) -> Value {
+    let variables = find_variable_names(server_url);
+    if variables.is_empty() {
+        json!({"url": server_url})
+    } else {
+        let mut server_variables = Map::new();
+        for name in variables {
+            server_variables.insert(name, json!({"default": ""}));
+        }
+        json!({"url": to_path_template(server_url), "variables": server_variables})
+    }
+}
+
+/// turn `{{id}}` into `{id}` in path
+pub fn to_path_template(path: &str) -> String {
+    let mut template = path.to_string();
+    for name in find_variable_names(path) {
+        template = template.replace(&format!("{{{{{}}}}}", name), &format!("{{{}}}", name));
+    }
+    template
+}
+
+/// import OpenAPI 3 document in JSON or YAML, and http code with one request per operation
+/// and environments json with `baseUrl` of servers are returned
+pub fn import_openapi(spec_text: &str) -> Result<(String, Value), String> {
+    let spec: Value = match serde_json::from_str(spec_text) {
+        Ok(spec) => spec,
+        Err(_) => serde_yaml::from_str(spec_text).map_err(|e| format!("invalid OpenAPI document: {}", e))?,
+    };
+    if !spec["openapi"].as_str().unwrap_or_default().starts_with('3') {
+        return Err("only OpenAPI 3 document is supported".to_owned());
+    }
+    let mut variable_names: Vec<String> = Vec::new();
+    let mut code_blocks: Vec<String> = Vec::new();
+    if let Some(paths) = spec["paths"].as_object() {
+        for (path, path_item) in paths {
+            let path_item = resolve_ref(&spec, path_item);
+            for method in OPERATION_METHODS {
+                if let Some(operation) = path_item.get(method) {
+                    let mut parameters: Vec<&Value> = Vec::new();
+                    // operation parameters override path item parameters with same name and location
+                    for parameter in operation["parameters"].as_array().into_iter().flatten() {
+                        parameters.push(resolve_ref(&spec, parameter));
+                    }
+                    for parameter in path_item["parameters"].as_array().into_iter().flatten() {
+                        let parameter = resolve_ref(&spec, parameter);
+                        if !parameters.iter().any(|p| p["name"] == parameter["name"] && p["in"] == parameter["in"]) {
+                            parameters.push(parameter);
+                        }
+                    }
+                    code_blocks.push(to_request_code(&spec, path, method, operation, &parameters, &mut variable_names));
+                }
+            }
+        }
+    }
+    let http_code = format_http_code(&code_blocks.join("\n\n"));
+    // environments for servers
+    let mut envs = Map::new();
+    let servers = spec["servers"].as_array().cloned().unwrap_or_default();
+    for (i, server) in servers.iter().enumerate() {
+        let env_name = match server["description"].as_str() {
+            Some(description) => to_kebab_case(description),
+            None if servers.len() == 1 => "dev".to_owned(),
+            None => format!("server{}", i + 1),
+        };
+        let mut base_url = server["url"].as_str().unwrap_or_default().trim_end_matches('/').to_string();
+        if let Some(server_variables) = server["variables"].as_object() {
+            for (name, variable) in server_variables {
+                let default_value = variable["default"].as_str().unwrap_or_default();
+                base_url = base_url.replace(&format!("{{{}}}", name), default_value);
+            }
+        }
+        let mut env = Map::new();
+        env.insert("baseUrl".to_owned(), json!(base_url));
+        for name in &variable_names {
+            env.insert(name.clone(), json!(""));
+        }
+        envs.insert(env_name, Value::Object(env));
+    }
+    Ok((http_code, Value::Object(envs)))
+}
+
+fn to_request_code(spec: &Value, path: &str, method: &str, operation: &Value, parameters: &[&Value], variable_names: &mut Vec<String>) -> String {
+    let mut code_lines: Vec<String> = Vec::new();
+    let comment = operation["summary"].as_str().or_else(|| operation["description"].as_str()).unwrap_or_default();
+    code_lines.push(format!("### {}", comment.lines().next().unwrap_or_default()));
+    let name = match operation["operationId"].as_str() {
+        Some(operation_id) => to_kebab_case(operation_id),
+        None => format!("{}-{}", method, to_kebab_case(path)),
+    };
+    code_lines.push(format!("//@name {}", name));
+    let mut url = format!("{{{{baseUrl}}}}{}", path.replace('{', "{{").replace('}', "}}"));
+    let mut query_pairs: Vec<String> = Vec::new();
+    let mut header_lines: Vec<String> = Vec::new();
+    for parameter in parameters {
+        let parameter_name = parameter["name"].as_str().unwrap_or_default();
+        let variable_name = parameter_name.replace(['-', '.'], "_");
+        match parameter["in"].as_str().unwrap_or_default() {
+            "path" => url = url.replace(&format!("{{{{{}}}}}", parameter_name), &format!("{{{{{}}}}}", variable_name)),
+            "query" => query_pairs.push(format!("{}={{{{{}}}}}", parameter_name, variable_name)),
+            "header" => header_lines.push(format!("{}: {{{{{}}}}}", parameter_name, variable_name)),
+            _ => continue,
+        }
+        if !variable_names.contains(&variable_name) {
+            variable_names.push(variable_name);
+        }
+    }
+    if !query_pairs.is_empty() {
+        url.push('?');
+        url.push_str(&query_pairs.join("&"));
+    }
+    code_lines.push(format!("{} {}", method.to_uppercase(), url));
+    code_lines.extend(header_lines);
+    let request_body = resolve_ref(spec, &operation["requestBody"]);
+    if let Some(content) = request_body["content"].as_object() {
+        let content_type = if content.contains_key("application/json") {
+            "application/json"
+        } else {
+            content.keys().next().map(|key| key.as_str()).unwrap_or("application/json")
+        };
+        let media_type = &content[content_type];
+        let example = if !media_type["example"].is_null() {
+            media_type["example"].clone()
+        } else if let Some(example) = media_type["examples"].as_object().and_then(|examples| examples.values().next()) {
+            resolve_ref(spec, example)["value"].clone()
+        } else {
+            to_example(spec, &media_type["schema"], 0)
+        };
+        code_lines.push(format!("Content-Type: {}", content_type));
+        code_lines.push("".to_owned());
+        match example {
+            Value::String(text) => code_lines.push(text),
+            example => code_lines.push(serde_json::to_string_pretty(&example).unwrap()),
+        }
+    }
+    code_lines.join("\n")
+}
+
+/// example value from schema with `example`, `examples`, `default`, `enum` and type
+fn to_example(spec: &Value, schema: &Value, depth: u32) -> Value {
+    let schema = resolve_ref(spec, schema);
+    if depth > 8 || !schema.is_object() {
+        return Value::Null;
+    }
+    if !schema["example"].is_null() {
+        return schema["example"].clone();
+    }
+    if let Some(example) = schema["examples"].as_array().and_then(|examples| examples.first()) {
+        return example.clone();
+    }
+    if !schema["default"].is_null() {
+        return schema["default"].clone();
+    }
+    if let Some(value) = schema["enum"].as_array().and_then(|values| values.first()) {
+        return value.clone();
+    }
+    if let Some(all_of) = schema["allOf"].as_array() {
+        let mut fields = Map::new();
+        for sub_schema in all_of {
+            if let Value::Object(sub_fields) = to_example(spec, sub_schema, depth + 1) {
+                fields.extend(sub_fields);
+            }
+        }
+        return Value::Object(fields);
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(sub_schema) = schema[key].as_array().and_then(|schemas| schemas.first()) {
+            return to_example(spec, sub_schema, depth + 1);
+        }
+    }
+    let schema_type = match &schema["type"] {
+        Value::Array(types) => types.iter().filter_map(|t| t.as_str()).find(|t| *t != "null").unwrap_or("null"),
+        schema_type => schema_type.as_str().unwrap_or(if schema["properties"].is_object() { "object" } else { "string" }),
+    };
+    match schema_type {
+        "object" => {
+            let mut fields = Map::new();
+            for (name, property) in schema["properties"].as_object().into_iter().flatten() {
+                fields.insert(name.clone(), to_example(spec, property, depth + 1));
+            }
+            Value::Object(fields)
+        }
+        "array" => json!([to_example(spec, &schema["items"], depth + 1)]),
+        "integer" | "number" => json!(0),
+        "boolean" => json!(false),
+        "null" => Value::Null,
+        _ => json!(""),
+    }
+}
+
+/// resolve local reference, such as `#/components/schemas/User`
+fn resolve_ref<'a>(spec: &'a Value, value: &'a Value) -> &'a Value {
+    let mut value = value;
+    // limit reference chain to avoid circular references
+    for _ in 0..8 {
+        match value["$ref"].as_str().and_then(|reference| reference.strip_prefix('#')) {
+            Some(pointer) => match spec.pointer(pointer) {
+                Some(target) => value = target,
+                None => break,
+            },
+            None => break,
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_http_code;
+
+    #[test]
+    fn test_to_openapi() {
+        let http_code = r#"
+### get user
+//@name get-user
+GET https://{{host}}/users/{{id}}?fields={{fields}}&lang=en
+X-Request-Id: {{request-id}}
+
+### update user
+//@name update-user
+PUT https://{{host}}/users/{{id}}
+Content-Type: application/json
+
+{
+"name": "{{nick}}",
+"age": 42,
+"tags": ["admin"]
+}
+"#;
+        let requests = parse_http_code(http_code);
+        let doc = to_openapi(&requests, "users");
+        println!("{}", to_openapi_yaml(&requests, "users"));
+        assert_eq!(doc["servers"][0]["url"], "https://{host}");
+        let path_item = &doc["paths"]["/users/{id}"];
+        assert_eq!(path_item["get"]["operationId"], "get-user");
+        assert_eq!(path_item["get"]["parameters"].as_array().unwrap().len(), 4);
+        assert_eq!(path_item["put"]["requestBody"]["content"]["application/json"]["schema"]["properties"]["age"]["type"], "integer");
+    }
+
+    #[test]
+    fn test_import_openapi() {
+        let spec_text = r#"
+openapi: 3.0.1
+info:
+  title: users
+  version: 1.0.0
+servers:
+  - url: https://{region}.example.com/api/
+    description: Production
+    variables:
+      region:
+        default: us
+paths:
+  /users/{user-id}:
+    parameters:
+      - name: user-id
+        in: path
+        required: true
+        schema:
+          type: string
+    get:
+      summary: get user
+      operationId: getUser
+      parameters:
+        - $ref: '#/components/parameters/Lang'
+        - name: X-Request-Id
+          in: header
+          schema:
+            type: string
+    put:
+      summary: update user
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: '#/components/schemas/User'
+    patch:
+      summary: patch user
+      operationId: patchUser
+      requestBody:
+        content:
+          application/json:
+            example:
+              age: 42
+    head:
+      operationId: checkUser
+components:
+  parameters:
+    Lang:
+      name: lang
+      in: query
+      schema:
+        type: string
+  schemas:
+    User:
+      type: object
+      properties:
+        name:
+          type: string
+          example: linux_china
+        age:
+          type: integer
+        tags:
+          type: array
+          items:
+            type: string
+"#;
+        let (http_code, envs) = import_openapi(spec_text).unwrap();
+        println!("{}", http_code);
+        let requests = crate::parser::parse_http_code(&http_code);
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[0].name, "get_user");
+        assert_eq!(requests[0].path, "{{baseUrl}}/users/{{user_id}}?lang={{lang}}");
+        assert_eq!(requests[0].get_header_value("X-Request-Id"), Some("{{X_Request_Id}}"));
+        assert_eq!(requests[1].name, "put_users_user_id");
+        assert_eq!(requests[1].body.as_deref(), Some("{\n\"name\": \"linux_china\",\n\"age\": 0,\n\"tags\": [\n\"\"\n]\n}"));
+        assert_eq!(requests[2].method, "HEAD");
+        assert_eq!(requests[2].name, "check_user");
+        assert_eq!(requests[3].method, "PATCH");
+        assert_eq!(requests[3].name, "patch_user");
+        assert_eq!(requests[3].tags, vec!["name patch-user"]);
+        assert_eq!(requests[3].body.as_deref(), Some("{\n\"age\": 42\n}"));
+        assert_eq!(crate::parser::parse_http_code(&format_http_code(&http_code)), requests);
+        assert_eq!(envs["production"]["baseUrl"], "https://us.example.com/api");
+        assert_eq!(envs["production"]["user_id"], "");
+    }
+}
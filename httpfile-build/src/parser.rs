@@ -1,4 +1,4 @@
-use crate::models::{is_legal_method, HttpRequest, HttpHeader};
+use crate::models::{is_legal_method, HttpAssertion, HttpRequest, HttpHeader};
 
 pub fn is_request_line(line: &str) -> bool {
     let space_include = line.find(' ');
@@ -33,10 +33,11 @@ pub fn parse_http_code(http_code: &str) -> Vec<HttpRequest> {
         }
         // comment for httpRequest or new HttpRequest separator
         if line.starts_with("###") {
-            let comment = line.strip_prefix("###").unwrap().to_string();
+            let comment = line.strip_prefix("###").unwrap().trim().to_string();
+            let comment = if comment.is_empty() { None } else { Some(comment) };
             if !http_request.is_filled() {
                 // set comment for request
-                http_request.comment = Some(comment);
+                http_request.comment = comment;
             } else {
                 //start new request
                 http_request.cleanup_metadata();
@@ -44,8 +45,13 @@ pub fn parse_http_code(http_code: &str) -> Vec<HttpRequest> {
                 requests.push(http_request);
                 request_index += 1;
                 http_request = HttpRequest::new(request_index);
-                http_request.comment = Some(comment);
+                http_request.comment = comment;
             }
+        } else if http_request.is_filled() && line.starts_with("> assert ") {
+            // declarative assertion alike `> assert status == 200`
+            let expression = line.strip_prefix("> assert ").unwrap().trim().to_string();
+            http_request.assertions.push(HttpAssertion::new(line_number, expression));
+            http_request.body_started = true;
         } else if !http_request.body_started {
             // comment for httpRequest
             if line.starts_with('#') || line.starts_with("//") {
@@ -66,9 +72,13 @@ pub fn parse_http_code(http_code: &str) -> Vec<HttpRequest> {
                     }
                     http_request.tags.push(tag);
                 } else {
-                    // normal comment
-                    if http_request.comment.is_none() {
-                        http_request.comment = Some(line[2..].trim().to_string());
+                    // normal comment, and comments after request line are kept between headers
+                    if http_request.is_filled() {
+                        http_request.header_comments.push((http_request.headers.len(), comment));
+                    } else if http_request.comment.is_none() && !comment.is_empty() {
+                        http_request.comment = Some(comment);
+                    } else {
+                        http_request.comments.push(comment);
                     }
                 }
             } else if is_request_line(line) {
@@ -76,10 +86,11 @@ pub fn parse_http_code(http_code: &str) -> Vec<HttpRequest> {
                 let offset = line.find(' ').unwrap();
                 http_request.method = line[..offset].to_string();
                 http_request.request_line = line[(offset + 1)..].to_string();
+                http_request.request_line_number = line_number;
             } else if raw_line.starts_with("  ") || raw_line.starts_with('\t') {
                 //append request line parts in multi lines
                 http_request.append_request_line(line);
-            } else if line.contains(':') && http_request.body_started == false {
+            } else if line.contains(':') && !http_request.body_started {
                 // header line
                 let parts = line.splitn(2, ':').map(|s| s.trim()).collect::<Vec<&str>>();
                 if parts[0].contains(' ') {
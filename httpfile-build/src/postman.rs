@@ -0,0 +1,332 @@
+use serde_json::{json, Map, Value};
+use crate::curl::{to_multipart_body, FORM_BOUNDARY};
+use crate::formatter::format_http_code;
+use crate::models::{to_kebab_case, to_unique_name};
+
+/// import Postman Collection v2.1, and http code with collection variables are returned
+pub fn import_postman_collection(collection_text: &str) -> Result<(String, Map<String, Value>), String> {
+    let collection: Value = serde_json::from_str(collection_text).map_err(|e| format!("invalid Postman collection: {}", e))?;
+    let schema = collection["info"]["schema"].as_str().unwrap_or_default();
+    if !schema.contains("v2.1") && !schema.contains("v2.0") {
+        return Err("only Postman Collection v2.1 is supported".to_owned());
+    }
+    let mut names: Vec<String> = Vec::new();
+    let mut code_blocks: Vec<String> = Vec::new();
+    let items = collection["item"].as_array().cloned().unwrap_or_default();
+    collect_requests(&items, &[], &collection["auth"], &mut names, &mut code_blocks);
+    let mut variables = Map::new();
+    for variable in collection["variable"].as_array().into_iter().flatten() {
+        if let Some(key) = variable["key"].as_str() {
+            variables.insert(key.to_string(), to_text_value(&variable["value"]));
+        }
+    }
+    Ok((format_http_code(&code_blocks.join("\n\n")), variables))
+}
+
+/// import Postman environment, and environment name with variables are returned
+pub fn import_postman_environment(environment_text: &str) -> Result<(String, Map<String, Value>), String> {
+    let environment: Value = serde_json::from_str(environment_text).map_err(|e| format!("invalid Postman environment: {}", e))?;
+    let name = environment["name"].as_str().ok_or("name missing in Postman environment")?;
+    let mut variables = Map::new();
+    for value in environment["values"].as_array().into_iter().flatten() {
+        if value["enabled"].as_bool() == Some(false) {
+            continue;
+        }
+        if let Some(key) = value["key"].as_str() {
+            variables.insert(key.to_string(), to_text_value(&value["value"]));
+        }
+    }
+    Ok((to_kebab_case(name), variables))
+}
+
+/// walk folders recursively, and auth is inherited from parent folder or collection
+fn collect_requests(items: &[Value], folders: &[String], parent_auth: &Value, names: &mut Vec<String>, code_blocks: &mut Vec<String>) {
+    for item in items {
+        let item_name = item["name"].as_str().unwrap_or_default().to_string();
+        let auth = if item["auth"].is_object() { &item["auth"] } else { parent_auth };
+        if let Some(sub_items) = item["item"].as_array() {
+            let mut sub_folders = folders.to_vec();
+            sub_folders.push(item_name);
+            collect_requests(sub_items, &sub_folders, auth, names, code_blocks);
+        } else if !item["request"].is_null() {
+            let name = to_unique_name(&to_kebab_case(&item_name), names);
+            names.push(name.replace('-', "_"));
+            let mut comment_path = folders.to_vec();
+            comment_path.push(item_name);
+            code_blocks.push(to_request_code(item, &comment_path.join(" / "), &name, auth));
+        }
+    }
+}
+
+fn to_request_code(item: &Value, comment: &str, name: &str, parent_auth: &Value) -> String {
+    // request could be url text only
+    let request = if let Some(url) = item["request"].as_str() { json!({"method": "GET", "url": url}) } else { item["request"].clone() };
+    let mut code_lines: Vec<String> = vec![format!("### {}", comment)];
+    for line in script_lines(item, "prerequest") {
+        code_lines.push(format!("// {}", line));
+    }
+    code_lines.push(format!("//@name {}", name));
+    let body = &request["body"];
+    let body_mode = if body["disabled"].as_bool() == Some(true) { "" } else { body["mode"].as_str().unwrap_or_default() };
+    let method = if body_mode == "graphql" { "GRAPHQL".to_owned() } else { request["method"].as_str().unwrap_or("GET").to_uppercase() };
+    let mut url = to_url(&request["url"]);
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for header in request["header"].as_array().into_iter().flatten() {
+        if header["disabled"].as_bool() != Some(true) {
+            headers.push((header["key"].as_str().unwrap_or_default().to_string(), to_text_value(&header["value"]).as_str().unwrap_or_default().to_string()));
+        }
+    }
+    let auth = if request["auth"].is_object() { &request["auth"] } else { parent_auth };
+    if let Some((location, name, value)) = to_auth(auth) {
+        if location == "query" {
+            url.push(if url.contains('?') { '&' } else { '?' });
+            url.push_str(&format!("{}={}", name, value));
+        } else if !headers.iter().any(|(header_name, _)| header_name.eq_ignore_ascii_case(&name)) {
+            headers.push((name, value));
+        }
+    }
+    let has_content_type = headers.iter().any(|(header_name, _)| header_name.eq_ignore_ascii_case("Content-Type"));
+    let mut body_text: Option<String> = None;
+    match body_mode {
+        "raw" => {
+            let raw = body["raw"].as_str().unwrap_or_default();
+            if !raw.is_empty() {
+                if !has_content_type {
+                    let content_type = match body["options"]["raw"]["language"].as_str().unwrap_or("text") {
+                        "json" => "application/json",
+                        "xml" => "application/xml",
+                        "html" => "text/html",
+                        "javascript" => "application/javascript",
+                        _ => "text/plain",
+                    };
+                    headers.push(("Content-Type".to_owned(), content_type.to_owned()));
+                }
+                body_text = Some(raw.to_string());
+            }
+        }
+        "urlencoded" => {
+            let pairs: Vec<String> = enabled_params(&body["urlencoded"])
+                .map(|param| format!("{}={}", param["key"].as_str().unwrap_or_default(), to_text_value(&param["value"]).as_str().unwrap_or_default()))
+                .collect();
+            if !has_content_type {
+                headers.push(("Content-Type".to_owned(), "application/x-www-form-urlencoded".to_owned()));
+            }
+            body_text = Some(pairs.join("&"));
+        }
+        "formdata" => {
+            let form_fields: Vec<String> = enabled_params(&body["formdata"])
+                .map(|param| {
+                    let key = param["key"].as_str().unwrap_or_default();
+                    let mut form_field = if param["type"].as_str() == Some("file") {
+                        format!("{}=@{}", key, param["src"].as_str().unwrap_or_default())
+                    } else {
+                        format!("{}={}", key, to_text_value(&param["value"]).as_str().unwrap_or_default())
+                    };
+                    if let Some(content_type) = param["contentType"].as_str() {
+                        form_field.push_str(&format!(";type={}", content_type));
+                    }
+                    form_field
+                })
+                .collect();
+            headers.retain(|(header_name, _)| !header_name.eq_ignore_ascii_case("Content-Type"));
+            headers.push(("Content-Type".to_owned(), format!("multipart/form-data; boundary={}", FORM_BOUNDARY)));
+            body_text = Some(to_multipart_body(&form_fields));
+        }
+        "file" => {
+            if let Some(src) = body["file"]["src"].as_str() {
+                body_text = Some(format!("< {}", src));
+            }
+        }
+        "graphql" => {
+            let query = body["graphql"]["query"].as_str().unwrap_or_default().to_string();
+            let variables = body["graphql"]["variables"].as_str().unwrap_or_default().trim().to_string();
+            if variables.is_empty() || variables == "{}" {
+                body_text = Some(query);
+            } else {
+                body_text = Some(format!("{}\n\n{}", query, variables));
+            }
+        }
+        _ => {}
+    }
+    code_lines.push(format!("{} {}", method, url));
+    for (name, value) in headers {
+        code_lines.push(format!("{}: {}", name, value));
+    }
+    if let Some(body_text) = body_text {
+        code_lines.push("".to_owned());
+        code_lines.push(body_text);
+    }
+    let test_lines = script_lines(item, "test");
+    if !test_lines.is_empty() {
+        code_lines.push("".to_owned());
+        code_lines.push("> {%".to_owned());
+        code_lines.extend(test_lines);
+        code_lines.push("%}".to_owned());
+    }
+    to_http_variables(&code_lines.join("\n"))
+}
+
+/// url from text or url object, and path variables such as `:id` are turned into `{{id}}`
+fn to_url(url: &Value) -> String {
+    let raw = match url {
+        Value::String(raw) => raw.clone(),
+        url => url["raw"].as_str().unwrap_or_default().to_string(),
+    };
+    let (path, query) = match raw.split_once('?') {
+        Some((path, query)) => (path.to_string(), Some(query)),
+        None => (raw.clone(), None),
+    };
+    let path = path
+        .split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(name) if !name.is_empty() => format!("{{{{{}}}}}", name),
+            _ => segment.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join("/");
+    match query {
+        Some(query) => format!("{}?{}", path, query),
+        None => path,
+    }
+}
+
+/// auth header or query param as (location, name, value) from Postman auth block
+fn to_auth(auth: &Value) -> Option<(String, String, String)> {
+    let auth_type = auth["type"].as_str()?;
+    let attribute = |name: &str| -> String {
+        auth[auth_type]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|attribute| attribute["key"].as_str() == Some(name))
+            .map(|attribute| to_text_value(&attribute["value"]).as_str().unwrap_or_default().to_string())
+            .unwrap_or_default()
+    };
+    match auth_type {
+        "bearer" => Some(("header".to_owned(), "Authorization".to_owned(), format!("Bearer {}", attribute("token")))),
+        "basic" => Some(("header".to_owned(), "Authorization".to_owned(), format!("Basic {} {}", attribute("username"), attribute("password")))),
+        "digest" => Some(("header".to_owned(), "Authorization".to_owned(), format!("Digest {} {}", attribute("username"), attribute("password")))),
+        "apikey" => {
+            let location = if attribute("in") == "query" { "query" } else { "header" };
+            Some((location.to_owned(), attribute("key"), attribute("value")))
+        }
+        _ => None,
+    }
+}
+
+fn enabled_params(params: &Value) -> impl Iterator<Item = &Value> {
+    params.as_array().into_iter().flatten().filter(|param| param["disabled"].as_bool() != Some(true))
+}
+
+fn script_lines(item: &Value, listen: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for event in item["event"].as_array().into_iter().flatten() {
+        if event["listen"].as_str() == Some(listen) {
+            match &event["script"]["exec"] {
+                Value::Array(exec) => lines.extend(exec.iter().filter_map(|line| line.as_str()).map(|line| line.to_string())),
+                Value::String(exec) => lines.extend(exec.lines().map(|line| line.to_string())),
+                _ => {}
+            }
+        }
+    }
+    lines.retain(|line| !line.trim().is_empty());
+    lines
+}
+
+/// Postman dynamic variables to http file ones, such as `{{$guid}}` to `{{$uuid}}`
+fn to_http_variables(code: &str) -> String {
+    code.replace("{{$guid}}", "{{$uuid}}").replace("{{$randomUUID}}", "{{$uuid}}")
+}
+
+fn to_text_value(value: &Value) -> Value {
+    match value {
+        Value::String(_) => value.clone(),
+        Value::Null => json!(""),
+        other => json!(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_http_code;
+
+    #[test]
+    fn test_import_postman_collection() {
+        let collection_text = r#"{
+  "info": {"name": "demo", "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"},
+  "auth": {"type": "bearer", "bearer": [{"key": "token", "value": "{{token}}", "type": "string"}]},
+  "item": [
+    {
+      "name": "users",
+      "item": [
+        {
+          "name": "Get User",
+          "event": [{"listen": "test", "script": {"exec": ["pm.test(\"ok\", function () {", "pm.response.to.have.status(200);", "});"]}}],
+          "request": {"method": "GET", "url": {"raw": "{{baseUrl}}/users/:id?lang=en"}}
+        },
+        {
+          "name": "Create User",
+          "request": {
+            "method": "POST",
+            "auth": {"type": "basic", "basic": [{"key": "username", "value": "admin"}, {"key": "password", "value": "secret"}]},
+            "header": [{"key": "X-Trace", "value": "{{$guid}}"}, {"key": "X-Disabled", "value": "1", "disabled": true}],
+            "body": {"mode": "raw", "raw": "{\"name\": \"{{nick}}\"}", "options": {"raw": {"language": "json"}}},
+            "url": "{{baseUrl}}/users"
+          }
+        },
+        {
+          "name": "Patch User",
+          "request": {
+            "method": "PATCH",
+            "body": {"mode": "raw", "raw": "{\"age\": 42}", "options": {"raw": {"language": "json"}}},
+            "url": "{{baseUrl}}/users/:id"
+          }
+        },
+        {
+          "name": "User Options",
+          "request": {"method": "OPTIONS", "url": "{{baseUrl}}/users"}
+        }
+      ]
+    },
+    {
+      "name": "Upload",
+      "request": {
+        "method": "POST",
+        "body": {"mode": "formdata", "formdata": [{"key": "name", "value": "demo", "type": "text"}, {"key": "file", "src": "./demo.png", "type": "file"}]},
+        "url": "{{baseUrl}}/upload"
+      }
+    }
+  ],
+  "variable": [{"key": "baseUrl", "value": "https://httpbin.org"}]
+}"#;
+        let (http_code, variables) = import_postman_collection(collection_text).unwrap();
+        println!("{}", http_code);
+        let requests = parse_http_code(&http_code);
+        assert_eq!(requests.len(), 5);
+        assert_eq!(requests[0].comment.as_deref(), Some("users / Get User"));
+        assert_eq!(requests[0].name, "get_user");
+        assert_eq!(requests[0].path, "{{baseUrl}}/users/{{id}}?lang=en");
+        assert_eq!(requests[0].get_header_value("Authorization"), Some("Bearer {{token}}"));
+        assert_eq!(requests[0].js_code.as_deref(), Some("pm.test(\"ok\", function () {\npm.response.to.have.status(200);\n});"));
+        assert_eq!(requests[1].get_header_value("Authorization"), Some("Basic admin secret"));
+        assert_eq!(requests[1].get_header_value("X-Trace"), Some("{{$uuid}}"));
+        assert_eq!(requests[1].get_header_value("X-Disabled"), None);
+        assert_eq!(requests[1].get_header_value("Content-Type"), Some("application/json"));
+        assert_eq!(requests[2].method, "PATCH");
+        assert_eq!(requests[2].name, "patch_user");
+        assert_eq!(requests[2].body.as_deref(), Some("{\n\"age\": 42\n}"));
+        assert_eq!(requests[3].method, "OPTIONS");
+        assert_eq!(requests[3].name, "user_options");
+        assert!(requests[4].body.as_ref().unwrap().contains("filename=\"demo.png\""));
+        assert_eq!(variables["baseUrl"], "https://httpbin.org");
+    }
+
+    #[test]
+    fn test_import_postman_environment() {
+        let environment_text = r#"{"name": "Staging Env", "values": [{"key": "baseUrl", "value": "https://staging.example.com", "enabled": true}, {"key": "debug", "value": true, "enabled": false}]}"#;
+        let (name, variables) = import_postman_environment(environment_text).unwrap();
+        assert_eq!(name, "staging-env");
+        assert_eq!(variables.len(), 1);
+    }
+}
@@ -0,0 +1,263 @@
+use std::path::Path;
+use serde_json::Value;
+use crate::models::HttpRequest;
+
+const RUST_KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn", "for", "if", "impl", "in",
+    "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "Self",
+];
+
+/// infer types of requests, and requests referenced by `{{name.response.body.$.token}}` are skipped to keep `Response` for dependent requests
+pub fn infer_types(requests: &mut [HttpRequest], httpfile_dir: &Path) -> Result<Vec<String>, String> {
+    let referenced_names: Vec<String> = requests.iter().flat_map(|request| request.dependencies()).collect();
+    let mut type_codes: Vec<String> = Vec::new();
+    for request in requests.iter_mut().filter(|request| !referenced_names.contains(&request.name)) {
+        let type_code = infer_request_types(request, httpfile_dir)?;
+        if !type_code.is_empty() {
+            type_codes.push(type_code);
+        }
+    }
+    Ok(type_codes)
+}
+
+/// infer body type from json body and response type from `<> ./response.json`, and code of structs is returned.
+/// body with dynamic variables, such as `{{$uuid}}`, is kept as template because values are generated for each call.
+/// missing response example, or `.json` example which is not json, is returned as error
+pub fn infer_request_types(request: &mut HttpRequest, httpfile_dir: &Path) -> Result<String, String> {
+    let mut struct_codes: Vec<String> = Vec::new();
+    let type_prefix = to_pascal_case(&request.name);
+    let is_json_body = request.get_header_value("Content-Type").is_some_and(|content_type| content_type.contains("json"));
+    let has_dynamic_variables = request.body.as_ref().is_some_and(|body| body.contains("{{$"));
+    if request.method != "GRAPHQL" && is_json_body && !has_dynamic_variables {
+        if let Some(json) = request.body.as_ref().and_then(|body| serde_json::from_str::<Value>(body).ok()) {
+            if json.is_object() || json.is_array() {
+                let (type_name, struct_code) = infer_rust_types(&format!("{}Request", type_prefix), &json);
+                request.body_type = Some(type_name);
+                struct_codes.push(struct_code);
+            }
+        }
+    }
+    if request.get_tag_value("response-type").is_none() {
+        for response_ref in &request.response_refs {
+            let response_file = httpfile_dir.join(response_ref);
+            let response_text = std::fs::read_to_string(&response_file)
+                .map_err(|e| format!("request `{}`: failed to read response example {}: {}", request.name, response_file.display(), e))?;
+            match serde_json::from_str::<Value>(&response_text) {
+                Ok(json) => {
+                    let (type_name, struct_code) = infer_rust_types(&format!("{}Response", type_prefix), &json);
+                    request.tags.push(format!("response-type {}", type_name));
+                    struct_codes.push(struct_code);
+                    break;
+                }
+                Err(e) if response_ref.ends_with(".json") => {
+                    return Err(format!("request `{}`: illegal json in response example {}: {}", request.name, response_file.display(), e));
+                }
+                // examples of other formats, such as xml, are not inferred
+                Err(_) => {}
+            }
+        }
+    }
+    struct_codes.retain(|code| !code.is_empty());
+    Ok(struct_codes.join("\n\n"))
+}
+
+/// infer serde structs from json example, and root type with code of structs are returned
+pub fn infer_rust_types(type_name: &str, json: &Value) -> (String, String) {
+    let mut structs: Vec<(String, String)> = Vec::new();
+    let root_type = to_rust_type(type_name, json, &mut structs);
+    let struct_codes = structs.into_iter().map(|(_, code)| code).collect::<Vec<String>>();
+    (root_type, struct_codes.join("\n\n"))
+}
+
+fn to_rust_type(type_name: &str, json: &Value, structs: &mut Vec<(String, String)>) -> String {
+    match json {
+        Value::Null => "Option<serde_json::Value>".to_owned(),
+        Value::Bool(_) => "bool".to_owned(),
+        Value::Number(number) => {
+            if number.is_f64() {
+                "f64".to_owned()
+            } else {
+                "i64".to_owned()
+            }
+        }
+        // `{{variable}}` in json text is taken as string field
+        Value::String(_) => "String".to_owned(),
+        Value::Array(items) => match items.first() {
+            Some(item) => format!("Vec<{}>", to_rust_type(&format!("{}Item", type_name), item, structs)),
+            None => "Vec<serde_json::Value>".to_owned(),
+        },
+        Value::Object(fields) => {
+            let mut struct_name = type_name.to_string();
+            let mut index = 1;
+            while structs.iter().any(|(name, _)| name == &struct_name) {
+                index += 1;
+                struct_name = format!("{}{}", type_name, index);
+            }
+            // reserve the name before nested structs
+            structs.push((struct_name.clone(), String::new()));
+            let mut code_lines: Vec<String> = vec![
+                "#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]".to_owned(),
+                format!("pub struct {} {{", struct_name),
+            ];
+            let mut field_names: Vec<String> = Vec::new();
+            for (name, value) in fields {
+                // keys with same field name, such as `userId` and `user_id`, get suffix
+                let mut field_name = to_field_name(name);
+                let mut index = 1;
+                while field_names.contains(&field_name) {
+                    index += 1;
+                    field_name = format!("{}_{}", to_field_name(name).trim_start_matches("r#"), index);
+                }
+                field_names.push(field_name.clone());
+                let field_type = to_rust_type(&format!("{}{}", struct_name, to_pascal_case(name)), value, structs);
+                if field_name.trim_start_matches("r#") != name {
+                    code_lines.push(format!("    #[serde(rename = \"{}\")]", name.replace('"', "\\\"")));
+                }
+                code_lines.push(format!("    pub {}: {},", field_name, field_type));
+            }
+            code_lines.push("}".to_owned());
+            let code = code_lines.join("\n");
+            if let Some(item) = structs.iter_mut().find(|(name, _)| name == &struct_name) {
+                item.1 = code;
+            }
+            struct_name
+        }
+    }
+}
+
+/// snake case field name, such as `user_id` for `userId`, and `r#type` for `type`
+fn to_field_name(name: &str) -> String {
+    let mut field_name = String::new();
+    let mut previous_lowercase = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && previous_lowercase {
+            field_name.push('_');
+        }
+        previous_lowercase = c.is_ascii_lowercase() || c.is_ascii_digit();
+        field_name.push(if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' });
+    }
+    let field_name = field_name.trim_matches('_').to_string();
+    if field_name.is_empty() || field_name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("field_{}", field_name)
+    } else if RUST_KEYWORDS.contains(&field_name.as_str()) {
+        format!("r#{}", field_name)
+    } else {
+        field_name
+    }
+}
+
+/// pascal case type name, such as `PostTest` for `post_test`
+pub fn to_pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            format!("{}{}", first, chars.as_str())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_http_code;
+
+    #[test]
+    fn test_infer_rust_types() {
+        let json: Value = serde_json::from_str(r#"{"userId": 1, "type": "{{kind}}", "score": 9.5, "tags": [{"name": "admin"}], "extra": null}"#).unwrap();
+        let (type_name, code) = infer_rust_types("PostTestRequest", &json);
+        println!("{}", code);
+        assert_eq!(type_name, "PostTestRequest");
+        assert!(code.contains("    #[serde(rename = \"userId\")]\n    pub user_id: i64,"));
+        assert!(code.contains("    pub r#type: String,"));
+        assert!(code.contains("    pub tags: Vec<PostTestRequestTagsItem>,"));
+        assert!(code.contains("pub struct PostTestRequestTagsItem {\n    pub name: String,\n}"));
+        let json: Value = serde_json::from_str(r#"{"user_id": 1, "userId": 2, "type": "a", "Type": "b"}"#).unwrap();
+        let (_, code) = infer_rust_types("User", &json);
+        println!("{}", code);
+        assert!(code.contains("    pub user_id: i64,\n    #[serde(rename = \"userId\")]\n    pub user_id_2: i64,"));
+        assert!(code.contains("    pub r#type: String,\n    #[serde(rename = \"Type\")]\n    pub type_2: String,"));
+    }
+
+    #[test]
+    fn test_infer_types_skipped() {
+        let http_code = r#"
+### login
+//@name login
+POST https://{{host}}/login
+Content-Type: application/json
+
+{"user": "{{user}}"}
+
+<> ./login.json
+
+### create order
+//@name create-order
+POST https://{{host}}/orders
+Content-Type: application/json
+Authorization: Bearer {{login.response.body.$.token}}
+
+{"id": "{{$uuid}}", "amount": 42}
+"#;
+        let httpfile_dir = std::env::temp_dir().join("httpfile-infer-types");
+        std::fs::create_dir_all(&httpfile_dir).unwrap();
+        std::fs::write(httpfile_dir.join("login.json"), r#"{"token": "abc"}"#).unwrap();
+        let mut requests = parse_http_code(http_code);
+        let type_codes = infer_types(&mut requests, &httpfile_dir).unwrap();
+        assert!(type_codes.is_empty());
+        assert!(requests[0].body_type.is_none());
+        assert!(requests[0].get_tag_value("response-type").is_none());
+        assert!(requests[1].body_type.is_none());
+        crate::chain::validate_dependencies(&requests).unwrap();
+    }
+
+    #[test]
+    fn test_infer_request_types() {
+        let http_code = r#"
+### post test
+//@name post-test
+POST https://{{host}}/post
+Content-Type: application/json
+
+{
+"name": "{{nick}}",
+"age": 42
+}
+"#;
+        let mut requests = parse_http_code(http_code);
+        let code = infer_request_types(&mut requests[0], Path::new(".")).unwrap();
+        assert!(code.contains("pub struct PostTestRequest {"));
+        let rust_code = requests[0].to_rust_code();
+        println!("{}", rust_code);
+        assert!(rust_code.starts_with("pub async fn post_test(params: &HashMap<&str, &str>, body: &PostTestRequest) -> httpfile::Result<Response> {"));
+        assert!(rust_code.contains("let body = httpfile::to_json(location, body)?;"));
+    }
+
+    #[test]
+    fn test_infer_response_example_error() {
+        let http_code = r#"
+### my ip
+//@name my-ip
+GET https://httpbin.org/ip
+
+<> ./missing-ip.json
+
+### users
+//@name users
+GET https://httpbin.org/users
+
+<> ./users.json
+"#;
+        let httpfile_dir = std::env::temp_dir().join("httpfile-infer-example");
+        std::fs::create_dir_all(&httpfile_dir).unwrap();
+        std::fs::write(httpfile_dir.join("users.json"), "[{\"id\": 1,}]").unwrap();
+        let mut requests = parse_http_code(http_code);
+        let error = infer_request_types(&mut requests[0], &httpfile_dir).unwrap_err();
+        println!("{}", error);
+        assert!(error.starts_with("request `my_ip`: failed to read response example "));
+        let error = infer_types(&mut requests[1..], &httpfile_dir).unwrap_err();
+        assert!(error.starts_with("request `users`: illegal json in response example "));
+    }
+}
@@ -0,0 +1,307 @@
+mod runner;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use clap::{Parser, Subcommand};
+use httpfile::chain::CapturedResponse;
+use httpfile::environment::{load_environment, ENV_FILE_NAME};
+use httpfile::oauth2::{auth_token, load_auth_configs, parse_auth_token_variable, register_auth_configs};
+use httpfile::proxy::{load_proxy_configuration, proxy_client_builder, register_proxy_configuration};
+use httpfile::tls::{load_ssl_configuration, register_ssl_configuration};
+use httpfile_build::curl::{import_curl_command, to_curl_command};
+use httpfile_build::graphql::include_fragments;
+use httpfile_build::models::HttpRequest;
+use httpfile_build::openapi::import_openapi;
+use httpfile_build::postman::{import_postman_collection, import_postman_environment};
+use httpfile_build::parser::parse_http_code;
+
+#[derive(Parser, Debug)]
+#[command(name = "httpfile", version, about = "Run http requests in http file")]
+struct Cli {
+    /// http file to load requests from
+    #[arg(short = 'f', long = "file", global = true, default_value = "index.http")]
+    httpfile: PathBuf,
+    /// environment name declared in http-client.env.json
+    #[arg(short, long, global = true)]
+    env: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List requests in http file
+    List,
+    /// Run requests by name or index, and pick one interactively without targets
+    Run {
+        /// request names or indexes, such as `my-ip` or `1`
+        targets: Vec<String>,
+        /// run all requests in http file
+        #[arg(short, long)]
+        all: bool,
+    },
+    /// Export requests as curl command lines
+    Curl {
+        /// request names or indexes, such as `my-ip` or `1`
+        targets: Vec<String>,
+        /// export all requests in http file
+        #[arg(short, long)]
+        all: bool,
+    },
+    /// Import requests into http file
+    Import {
+        #[command(subcommand)]
+        source: ImportSource,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ImportSource {
+    /// Import curl command line, and command is read from stdin if absent
+    Curl {
+        /// curl command line, such as `curl https://httpbin.org/ip`
+        command: Option<String>,
+    },
+    /// Import operations of OpenAPI 3 document, and servers are merged into http-client.env.json
+    Openapi {
+        /// OpenAPI document in JSON or YAML
+        spec_file: PathBuf,
+    },
+    /// Import Postman Collection v2.1, and variables are merged into http-client.env.json
+    Postman {
+        /// Postman collection json file
+        collection_file: PathBuf,
+        /// Postman environment json files
+        #[arg(long = "environment")]
+        environment_files: Vec<PathBuf>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    if let Command::Import { source } = &cli.command {
+        return import(&cli, source);
+    }
+    let httpfile_text = std::fs::read_to_string(&cli.httpfile)?;
+    let requests = parse_http_code(&httpfile_text);
+    match &cli.command {
+        Command::List => list_requests(&requests),
+        Command::Run { targets, all } => {
+            let mut variables = load_variables(&cli)?;
+            // dependencies of `{{name.response.body.$.token}}` references are run before selected requests
+            let selected_requests = runner::with_dependencies(&requests, select_requests(&requests, targets, *all)?)?;
+            // assertions are checked before any request is run, alike Builder
+            for request in &selected_requests {
+                runner::validate_assertions(request).map_err(|e| format!("{}: {}", cli.httpfile.display(), e))?;
+            }
+            let mut responses: HashMap<String, CapturedResponse> = HashMap::new();
+            let client = httpfile::client_builder().build()?;
+            let insecure_client = httpfile::client_builder().danger_accept_invalid_certs(true).build()?;
+            let httpfile_dir = cli.httpfile.parent().unwrap_or_else(|| Path::new("."));
+            let file = cli.httpfile.to_string_lossy();
+            for (i, request) in selected_requests.into_iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                fetch_auth_tokens(request, &mut variables).await?;
+                let location = runner::to_location(request, &file);
+                runner::resolve_references(&location, request, &responses, &mut variables)?;
+                let mut request = runner::render_request(request, &variables);
+                include_fragments(&mut request, httpfile_dir)?;
+                println!("### {}", request.name);
+                println!("{} {}", request.method, request.uri.as_deref().unwrap_or(&request.path));
+                println!();
+                if request.method == "WEBSOCKET" {
+                    runner::print_messages(&location, &request).await?;
+                    continue;
+                }
+                if request.method == "GRAPHQL" && request.graphql_operation().is_some_and(|operation| operation.operation_type == "subscription") {
+                    runner::print_payloads(&location, &request).await?;
+                    continue;
+                }
+                if request.is_broker_request() {
+                    let messages = runner::run_broker_request(&location, &request).await?;
+                    runner::check_assertions(&location, &request, &messages)?;
+                    responses.insert(request.name.clone(), messages);
+                    continue;
+                }
+                if request.is_command_request() {
+                    let output = CapturedResponse::from(runner::print_command_output(&location, &request).await?);
+                    runner::check_assertions(&location, &request, &output)?;
+                    responses.insert(request.name.clone(), output);
+                    continue;
+                }
+                if request.method == "GRPC" {
+                    let json = runner::print_grpc_response(&location, &request, httpfile_dir).await?;
+                    runner::check_assertions(&location, &request, &json)?;
+                    responses.insert(request.name.clone(), json);
+                    continue;
+                }
+                let insecure = request.get_tag_value("insecure").is_some();
+                let proxy_client = match request.get_tag_value("proxy") {
+                    Some(proxy) => Some(proxy_client_builder(proxy, &[])?.danger_accept_invalid_certs(insecure).build()?),
+                    None => None,
+                };
+                let client = proxy_client.as_ref().unwrap_or(if insecure { &insecure_client } else { &client });
+                if request.get_tag_value("sse").is_some() {
+                    runner::print_events(&location, runner::to_request_builder(client, &request)).await?;
+                    continue;
+                }
+                let response = runner::execute(client, &request).await?;
+                let captured = runner::print_response(response).await?;
+                runner::check_assertions(&location, &request, &captured)?;
+                responses.insert(request.name.clone(), captured);
+            }
+        }
+        Command::Curl { targets, all } => {
+            let mut variables = load_variables(&cli)?;
+            let selected_requests = select_requests(&requests, targets, *all)?;
+            let httpfile_dir = cli.httpfile.parent().unwrap_or_else(|| Path::new("."));
+            for request in selected_requests {
+                fetch_auth_tokens(request, &mut variables).await?;
+                let mut request = runner::render_request(request, &variables);
+                include_fragments(&mut request, httpfile_dir)?;
+                println!("{}", to_curl_command(&request));
+            }
+        }
+        Command::Import { .. } => {}
+    }
+    Ok(())
+}
+
+fn import(cli: &Cli, source: &ImportSource) -> Result<(), Box<dyn Error>> {
+    let httpfile_text = if cli.httpfile.exists() {
+        std::fs::read_to_string(&cli.httpfile)?
+    } else {
+        String::new()
+    };
+    let httpfile_text = match source {
+        ImportSource::Curl { command } => {
+            let command = match command {
+                Some(command) => command.clone(),
+                None => {
+                    let mut command = String::new();
+                    std::io::stdin().read_to_string(&mut command)?;
+                    command
+                }
+            };
+            import_curl_command(&httpfile_text, &command)?
+        }
+        ImportSource::Openapi { spec_file } => {
+            let (http_code, envs) = import_openapi(&std::fs::read_to_string(spec_file)?)?;
+            merge_env_file(cli, envs)?;
+            append_http_code(&httpfile_text, &http_code)
+        }
+        ImportSource::Postman { collection_file, environment_files } => {
+            let (http_code, collection_variables) = import_postman_collection(&std::fs::read_to_string(collection_file)?)?;
+            let mut envs = serde_json::Map::new();
+            for environment_file in environment_files {
+                let (env_name, variables) = import_postman_environment(&std::fs::read_to_string(environment_file)?)?;
+                // environment variables override collection variables
+                let mut env = collection_variables.clone();
+                env.extend(variables);
+                envs.insert(env_name, serde_json::Value::Object(env));
+            }
+            if envs.is_empty() && !collection_variables.is_empty() {
+                envs.insert("dev".to_owned(), serde_json::Value::Object(collection_variables));
+            }
+            merge_env_file(cli, serde_json::Value::Object(envs))?;
+            append_http_code(&httpfile_text, &http_code)
+        }
+    };
+    std::fs::write(&cli.httpfile, httpfile_text)?;
+    Ok(())
+}
+
+fn append_http_code(httpfile_text: &str, http_code: &str) -> String {
+    let mut code = httpfile_text.trim_end().to_string();
+    if !code.is_empty() {
+        code.push_str("\n\n");
+    }
+    code.push_str(http_code);
+    code
+}
+
+/// merge environments into http-client.env.json beside http file, and existing variables are kept
+fn merge_env_file(cli: &Cli, envs: serde_json::Value) -> Result<(), Box<dyn Error>> {
+    let serde_json::Value::Object(envs) = envs else { return Ok(()) };
+    if envs.is_empty() {
+        return Ok(());
+    }
+    let env_file = cli.httpfile.parent().map(|dir| dir.to_path_buf()).unwrap_or_default().join(ENV_FILE_NAME);
+    let mut env_json: serde_json::Map<String, serde_json::Value> = if env_file.exists() {
+        serde_json::from_str(&std::fs::read_to_string(&env_file)?)?
+    } else {
+        serde_json::Map::new()
+    };
+    for (env_name, variables) in envs {
+        let env = env_json.entry(env_name).or_insert_with(|| serde_json::json!({}));
+        if let (Some(env), serde_json::Value::Object(variables)) = (env.as_object_mut(), variables) {
+            for (name, value) in variables {
+                env.entry(name).or_insert(value);
+            }
+        }
+    }
+    std::fs::write(env_file, serde_json::to_string_pretty(&env_json)?)?;
+    Ok(())
+}
+
+/// load variables of the environment, and OAuth2 configs, `SSLConfiguration` and `ProxyConfiguration` of the environment are registered
+fn load_variables(cli: &Cli) -> std::io::Result<HashMap<String, String>> {
+    match &cli.env {
+        Some(env_name) => {
+            let env_dir = cli.httpfile.parent().map(|dir| dir.to_path_buf()).unwrap_or_default();
+            let variables = load_environment(&env_dir, env_name)?;
+            register_auth_configs(load_auth_configs(&env_dir, env_name)?);
+            if let Some(ssl_configuration) = load_ssl_configuration(&env_dir, env_name)? {
+                register_ssl_configuration(&ssl_configuration)?;
+            }
+            if let Some(proxy_configuration) = load_proxy_configuration(&env_dir, env_name)? {
+                register_proxy_configuration(&proxy_configuration)?;
+            }
+            Ok(variables)
+        }
+        None => Ok(HashMap::new()),
+    }
+}
+
+/// obtain OAuth2 tokens for `{{$auth.token("name")}}` variables in request
+async fn fetch_auth_tokens(request: &HttpRequest, variables: &mut HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+    for variable in request.auth_variable_names() {
+        if let Some(name) = parse_auth_token_variable(&variable) {
+            let token = auth_token(name).await.map_err(|message| format!("failed to obtain token for auth {}: {}", name, message))?;
+            variables.insert(variable, token);
+        }
+    }
+    Ok(())
+}
+
+fn select_requests<'a>(requests: &'a [HttpRequest], targets: &[String], all: bool) -> Result<Vec<&'a HttpRequest>, Box<dyn Error>> {
+    if all {
+        Ok(requests.iter().collect())
+    } else if targets.is_empty() {
+        Ok(vec![pick_request(requests)?])
+    } else {
+        Ok(runner::find_requests(requests, targets)?)
+    }
+}
+
+fn list_requests(requests: &[HttpRequest]) {
+    for request in requests {
+        let comment = request.comment.as_deref().unwrap_or_default();
+        println!("{:>3}  {:<24} {} {}  {}", request.index, request.name, request.method, request.path, comment);
+    }
+}
+
+fn pick_request(requests: &[HttpRequest]) -> Result<&HttpRequest, Box<dyn Error>> {
+    list_requests(requests);
+    print!("Please pick a request by name or index: ");
+    std::io::stdout().flush()?;
+    let mut target = String::new();
+    std::io::stdin().read_line(&mut target)?;
+    let mut found_requests = runner::find_requests(requests, &[target.trim().to_string()])?;
+    Ok(found_requests.remove(0))
+}
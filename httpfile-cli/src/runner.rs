@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use std::path::Path;
+use httpfile_build::{chain, grpc};
+use httpfile_build::models::HttpRequest;
+use httpfile::assertion::Assertion;
+use httpfile::auth;
+use httpfile::chain::CapturedResponse;
+use httpfile::shell::CommandOutput;
+use httpfile::RequestLocation;
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Method, RequestBuilder, Response, Result};
+
+/// find requests by name or index(starts from 1), such as `my-ip` or `2`
+pub fn find_requests<'a>(requests: &'a [HttpRequest], targets: &[String]) -> std::result::Result<Vec<&'a HttpRequest>, String> {
+    let mut found_requests = Vec::new();
+    for target in targets {
+        let request = if let Ok(index) = target.parse::<u32>() {
+            requests.iter().find(|request| request.index == index)
+        } else {
+            let name = target.trim_start_matches('@').replace('-', "_");
+            requests.iter().find(|request| request.name == name)
+        };
+        match request {
+            Some(request) => found_requests.push(request),
+            None => return Err(format!("request not found: {}", target)),
+        }
+    }
+    Ok(found_requests)
+}
+
+/// selected requests with their dependencies before them, and each request is included once
+pub fn with_dependencies<'a>(requests: &'a [HttpRequest], selected_requests: Vec<&'a HttpRequest>) -> std::result::Result<Vec<&'a HttpRequest>, String> {
+    let mut ordered_requests: Vec<&HttpRequest> = Vec::new();
+    for request in selected_requests {
+        for dependency in chain::dependency_order(requests, request)?.into_iter().chain([request]) {
+            if !ordered_requests.iter().any(|ordered_request| ordered_request.index == dependency.index) {
+                ordered_requests.push(dependency);
+            }
+        }
+    }
+    Ok(ordered_requests)
+}
+
+/// resolve `{{name.response.body.$.token}}` references with captured responses into variables
+pub fn resolve_references(location: &RequestLocation, request: &HttpRequest, responses: &HashMap<String, CapturedResponse>, variables: &mut HashMap<String, String>) -> httpfile::Result<()> {
+    let missing_response = CapturedResponse::default();
+    let references = request.response_references();
+    let references: Vec<(&str, &CapturedResponse)> = references
+        .iter()
+        .map(|reference| (reference.variable.as_str(), responses.get(&reference.request_name).unwrap_or(&missing_response)))
+        .collect();
+    variables.extend(httpfile::chain::resolve(location, &references)?);
+    Ok(())
+}
+
+/// reject assertions of requests whose output is not captured, such as `WEBSOCKET` and `@sse` requests,
+/// and messages of `SUB` request are captured by `httpfile run` after subscription closed
+pub fn validate_assertions(request: &HttpRequest) -> std::result::Result<(), String> {
+    if request.method == "SUB" {
+        return request.assertions.iter().try_for_each(|assertion| assertion.parse().map(|_| ()));
+    }
+    request.validate_assertions()
+}
+
+/// check `> assert` lines of request with captured response, and passed assertions are printed
+pub fn check_assertions(location: &RequestLocation, request: &HttpRequest, response: &CapturedResponse) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let mut parts = Vec::new();
+    for assertion in &request.assertions {
+        let (subject, operator, expected) = assertion.parse()?;
+        parts.push((assertion.line_number, subject, operator, expected));
+    }
+    let assertions: Vec<Assertion> = parts.iter().map(|(line, subject, operator, expected)| Assertion::new(*line, subject, operator, expected)).collect();
+    httpfile::assertion::check(location, response, &assertions)?;
+    if !assertions.is_empty() {
+        println!();
+        for assertion in &assertions {
+            println!("assert {}: passed", assertion.expression());
+        }
+    }
+    Ok(())
+}
+
+/// render variables in request with environment variables and dynamic variables, such as `$uuid`,
+/// and unknown variables are kept as they are
+pub fn render_request(request: &HttpRequest, variables: &HashMap<String, String>) -> HttpRequest {
+    let params: HashMap<&str, &str> = variables.iter().map(|(name, value)| (name.as_str(), value.as_str())).collect();
+    request.render(|name| {
+        if name.starts_with('$') || params.contains_key(name) {
+            httpfile::get_variable_value(&params, name)
+        } else {
+            format!("{{{{{}}}}}", name)
+        }
+    })
+}
+
+/// request builder with headers and body, and `Basic user password` is encoded
+pub fn to_request_builder(client: &Client, request: &HttpRequest) -> RequestBuilder {
+    let method = if request.method == "GRAPHQL" || request.method == "JSONRPC" {
+        Method::POST
+    } else {
+        Method::from_bytes(request.method.as_bytes()).unwrap_or(Method::GET)
+    };
+    let url = request.uri.clone().unwrap_or_else(|| request.path.clone());
+    let json_body = request.method == "GRAPHQL" || request.method == "JSONRPC";
+    let mut request_builder = client.request(method, url);
+    for header in &request.headers {
+        // Content-Type of GRAPHQL and JSONRPC is replaced with `application/json` whatever case of header name
+        if json_body && header.name.eq_ignore_ascii_case("Content-Type") {
+            continue;
+        }
+        if header.name.eq_ignore_ascii_case("Authorization") {
+            // digest credentials are answered after challenge
+            if auth::parse_digest_auth(&header.value).is_none() {
+                request_builder = request_builder.header(&header.name, auth::encode_basic_auth(&header.value));
+            }
+            continue;
+        }
+        request_builder = request_builder.header(&header.name, &header.value);
+    }
+    if json_body {
+        request_builder = request_builder.header("Content-Type", "application/json");
+    }
+    if let Some(body) = &request.body {
+        request_builder = request_builder.body(body.clone());
+    }
+    request_builder
+}
+
+pub async fn execute(client: &Client, request: &HttpRequest) -> Result<Response> {
+    let request_builder = to_request_builder(client, request);
+    let digest_credentials = request.get_header_value("Authorization").and_then(auth::parse_digest_auth);
+    match digest_credentials {
+        Some((user, password)) => auth::send_with_digest_auth(request_builder, &user, &password).await,
+        None => request_builder.send().await,
+    }
+}
+
+/// location of request for errors, and names are owned because requests are loaded at runtime
+pub fn to_location(request: &HttpRequest, file: &str) -> RequestLocation {
+    RequestLocation::owned(request.name.as_str(), file, request.request_line_number)
+}
+
+/// connect WebSocket server with messages in body, and print incoming messages until connection closed
+pub async fn print_messages(location: &RequestLocation, request: &HttpRequest) -> httpfile::Result<()> {
+    let url = request.uri.clone().unwrap_or_else(|| request.path.clone());
+    let mut headers = HeaderMap::new();
+    for header in &request.headers {
+        httpfile::insert_header(location, &mut headers, &header.name, &header.value)?;
+    }
+    let messages = request.body.as_deref().map(httpfile::websocket::parse_messages).unwrap_or_default();
+    let mut stream = httpfile::websocket::connect(location, &url, headers, messages).await?;
+    while let Some(message) = stream.next_message().await {
+        println!("{}", message?);
+    }
+    Ok(())
+}
+
+/// publish body of `PUB` request, or print messages of `SUB` request until subscription closed,
+/// and messages are captured alike response body with one message per line
+pub async fn run_broker_request(location: &RequestLocation, request: &HttpRequest) -> httpfile::Result<CapturedResponse> {
+    let uri = request.uri.as_deref().unwrap_or_default();
+    if request.method == "SUB" {
+        let mut stream = httpfile::broker::subscribe(location, uri, &request.path).await?;
+        let mut texts = Vec::new();
+        while let Some(message) = stream.next_message().await {
+            let message = message?;
+            println!("{}: {}", message.topic, message.text());
+            texts.push(message.text());
+        }
+        return Ok(to_captured_messages(&texts));
+    }
+    let mut headers = HeaderMap::new();
+    for header in request.headers.iter().filter(|header| !header.name.eq_ignore_ascii_case("URI")) {
+        httpfile::insert_header(location, &mut headers, &header.name, &header.value)?;
+    }
+    httpfile::broker::publish(location, uri, &request.path, headers, request.body.as_deref().unwrap_or_default()).await?;
+    println!("published to {}", request.path);
+    Ok(to_captured_messages(&[]))
+}
+
+/// messages of `SUB` request as captured response, such as `{{sub.response.body}}`
+pub fn to_captured_messages(texts: &[String]) -> CapturedResponse {
+    CapturedResponse { status: 200, headers: HeaderMap::new(), body: texts.join("\n") }
+}
+
+/// run script of `SHELL` or `SSH` request, and print stdout alike response body
+pub async fn print_command_output(location: &RequestLocation, request: &HttpRequest) -> httpfile::Result<CommandOutput> {
+    let mut headers = HeaderMap::new();
+    for header in &request.headers {
+        httpfile::insert_header(location, &mut headers, &header.name, &header.value)?;
+    }
+    let script = request.body.as_deref().unwrap_or_default();
+    let output = if request.method == "SSH" {
+        httpfile::shell::ssh(location, &request.path, request.get_tag_value("insecure").is_some(), &headers, script).await?
+    } else {
+        httpfile::shell::run(location, &request.path, &headers, script).await?
+    };
+    eprint!("{}", output.stderr);
+    match serde_json::from_str::<serde_json::Value>(&output.stdout) {
+        Ok(json) => println!("{}", serde_json::to_string_pretty(&json).unwrap()),
+        Err(_) => println!("{}", output.text()),
+    }
+    Ok(output)
+}
+
+/// subscribe GraphQL subscription over WebSocket, and print payloads until subscription completed
+pub async fn print_payloads(location: &RequestLocation, request: &HttpRequest) -> httpfile::Result<()> {
+    let url = request.uri.clone().unwrap_or_else(|| request.path.clone());
+    let mut headers = HeaderMap::new();
+    for header in &request.headers {
+        httpfile::insert_header(location, &mut headers, &header.name, &header.value)?;
+    }
+    let mut stream = httpfile::graphql::subscribe(location, &url, headers, request.body.as_deref().unwrap_or("{}")).await?;
+    while let Some(payload) = stream.next_payload().await {
+        println!("{}", serde_json::to_string_pretty(&payload?).unwrap());
+    }
+    Ok(())
+}
+
+/// call gRPC method with json body, and descriptors are compiled from `@proto` file or fetched by server reflection.
+/// json output is captured alike response body
+pub async fn print_grpc_response(location: &RequestLocation, request: &HttpRequest, httpfile_dir: &Path) -> std::result::Result<CapturedResponse, Box<dyn std::error::Error>> {
+    let url = request.uri.clone().unwrap_or_else(|| request.path.clone());
+    let mut headers = HeaderMap::new();
+    for header in &request.headers {
+        httpfile::insert_header(location, &mut headers, &header.name, &header.value)?;
+    }
+    let descriptor = match grpc::find_proto_file(request, httpfile_dir) {
+        Some(proto_file) => Some(grpc::compile_proto_descriptor(&proto_file)?),
+        None => None,
+    };
+    let body = request.body.as_deref().unwrap_or("{}");
+    let json: serde_json::Value = httpfile::grpc::call(location, &url, headers, body, descriptor.as_deref()).await?;
+    println!("{}", serde_json::to_string_pretty(&json).unwrap());
+    Ok(CapturedResponse::from(json))
+}
+
+/// print events of `@sse` request until server stops reconnection
+pub async fn print_events(location: &RequestLocation, request_builder: RequestBuilder) -> httpfile::Result<()> {
+    let mut stream = httpfile::sse::connect(location, request_builder).await?;
+    while let Some(event) = stream.next_event().await {
+        let event = event?;
+        if let Some(id) = &event.id {
+            println!("id: {}", id);
+        }
+        println!("event: {}", event.event);
+        for line in event.data.lines() {
+            println!("data: {}", line);
+        }
+        println!();
+    }
+    Ok(())
+}
+
+/// print status, headers and body, and json body is pretty printed. response is captured for later requests
+pub async fn print_response(response: Response) -> Result<CapturedResponse> {
+    println!("{:?} {}", response.version(), response.status());
+    for (name, value) in response.headers() {
+        println!("{}: {}", name, value.to_str().unwrap_or_default());
+    }
+    let status = response.status().as_u16();
+    let headers = response.headers().clone();
+    let text = response.text().await?;
+    if !text.is_empty() {
+        println!();
+        match serde_json::from_str::<serde_json::Value>(&text) {
+            Ok(json) => println!("{}", serde_json::to_string_pretty(&json).unwrap()),
+            Err(_) => println!("{}", text),
+        }
+    }
+    Ok(CapturedResponse { status, headers, body: text })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpfile_build::parser::parse_http_code;
+
+    #[test]
+    fn test_find_requests() {
+        let requests = parse_http_code(&std::fs::read_to_string("../httpfile-build/index.http").unwrap());
+        let targets = vec!["post-test".to_string(), "1".to_string()];
+        let found_requests = find_requests(&requests, &targets).unwrap();
+        assert_eq!(found_requests[0].name, "post_test");
+        assert_eq!(found_requests[1].name, "my_ip");
+        assert!(find_requests(&requests, &["missing".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_with_dependencies() {
+        let http_code = r#"
+### login
+//@name login
+POST https://httpbin.org/post
+
+### profile
+//@name profile
+GET https://httpbin.org/anything/{{login.response.body.$.json.id}}
+Authorization: Bearer {{login.response.headers.X-Token}}
+"#;
+        let requests = parse_http_code(http_code);
+        let ordered_requests = with_dependencies(&requests, vec![&requests[1], &requests[0]]).unwrap();
+        assert_eq!(ordered_requests.iter().map(|request| request.name.as_str()).collect::<Vec<&str>>(), vec!["login", "profile"]);
+        let mut headers = HeaderMap::new();
+        headers.insert("x-token", "abc".parse().unwrap());
+        let responses = HashMap::from([("login".to_string(), CapturedResponse { status: 200, headers, body: r#"{"json":{"id":1}}"#.to_string() })]);
+        let mut variables = HashMap::new();
+        resolve_references(&to_location(&requests[1], "index.http"), &requests[1], &responses, &mut variables).unwrap();
+        let request = render_request(&requests[1], &variables);
+        assert_eq!(request.uri.as_deref(), Some("https://httpbin.org/anything/1"));
+        assert_eq!(request.get_header_value("Authorization"), Some("Bearer abc"));
+    }
+
+    #[test]
+    fn test_render_request() {
+        let requests = parse_http_code(&std::fs::read_to_string("../httpfile-build/index.http").unwrap());
+        let variables = HashMap::from([("host".to_string(), "httpbin.org".to_string()), ("nick".to_string(), "Rust".to_string())]);
+        let request = render_request(&requests[1], &variables);
+        assert_eq!(request.uri.as_deref(), Some("https://httpbin.org/post"));
+        assert!(request.body.unwrap().contains(r#""name": "Rust""#));
+        // mistyped dynamic variable is kept instead of rendered as empty text
+        let request = render_request(&parse_http_code("GET https://httpbin.org/anything/{{$uuidd}}\n")[0], &variables);
+        assert_eq!(request.uri.as_deref(), Some("https://httpbin.org/anything/{{$uuidd}}"));
+    }
+
+    #[test]
+    fn test_to_request_builder() {
+        let http_code = "JSONRPC https://example.com/rpc eth_blockNumber\ncontent-type: application/json-rpc\n";
+        let request = render_request(&parse_http_code(http_code)[0], &HashMap::new());
+        let http_request = to_request_builder(&Client::new(), &request).build().unwrap();
+        assert_eq!(http_request.method(), Method::POST);
+        let content_types: Vec<&str> = http_request.headers().get_all("Content-Type").iter().map(|value| value.to_str().unwrap()).collect();
+        assert_eq!(content_types, vec!["application/json"]);
+    }
+
+    #[test]
+    fn test_validate_assertions() {
+        let http_code = r#"
+### orders
+//@name orders
+SUB orders
+URI: nats://localhost:4222
+
+> assert body contains id
+
+### chat
+//@name chat
+WEBSOCKET ws://localhost:8080/chat
+
+> assert body contains hello
+"#;
+        let requests = parse_http_code(http_code);
+        validate_assertions(&requests[0]).unwrap();
+        assert_eq!(validate_assertions(&requests[1]).unwrap_err(), "line 13: assertions are not supported for WEBSOCKET request `chat`");
+    }
+
+    #[test]
+    fn test_to_captured_messages() {
+        let texts = vec![r#"{"id":1}"#.to_string(), "bye".to_string()];
+        let captured = to_captured_messages(&texts);
+        assert_eq!(captured.status, 200);
+        assert_eq!(captured.body, "{\"id\":1}\nbye");
+        assert_eq!(captured.get(&RequestLocation::new("orders", "index.http", 3), "body").unwrap().as_deref(), Some("{\"id\":1}\nbye"));
+    }
+}
@@ -2,6 +2,8 @@ fn main() {
     println!("cargo:rerun-if-changed=index.http");
     httpfile_build::configure()
         .httpfile("index.http")
+        .infer_types(true)
+        .expect_status("2xx")
         .compile()
         .unwrap();
     println!("Code generation for index.http successful!!");
@@ -11,7 +11,7 @@ struct MyIp {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let json: MyIp = httpbin::my_ip().await?.json().await?;
+    let json: MyIp = httpbin::my_ip().await?;
     println!("{:?}", json);
     let params = HashMap::from([
         ("nick", "Rust"),
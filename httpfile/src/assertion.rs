@@ -0,0 +1,147 @@
+use std::cmp::Ordering;
+use reqwest::Response;
+use serde_json::Value;
+use crate::chain::CapturedResponse;
+use crate::{extract, Error, RequestLocation, Result};
+
+/// assertion of `> assert status == 200` line in http file, and expression is parsed by httpfile-build
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Assertion<'a> {
+    /// line number in http file
+    pub line: u32,
+    /// `status`, `body`, `header <Name>`, JSONPath such as `$.items.length`, or XPath such as `/user/id`
+    pub subject: &'a str,
+    /// `==`, `!=`, `>`, `>=`, `<`, `<=`, `contains` or `exists`
+    pub operator: &'a str,
+    pub expected: &'a str,
+}
+
+impl<'a> Assertion<'a> {
+    pub const fn new(line: u32, subject: &'a str, operator: &'a str, expected: &'a str) -> Assertion<'a> {
+        Assertion { line, subject, operator, expected }
+    }
+
+    /// expression alike `header Content-Type contains json`
+    pub fn expression(&self) -> String {
+        format!("{} {} {}", self.subject, self.operator, self.expected).trim_end().to_string()
+    }
+}
+
+/// check assertions in order, and first failed assertion is returned as `Assertion` error
+pub fn check(location: &RequestLocation, response: &CapturedResponse, assertions: &[Assertion]) -> Result<()> {
+    for assertion in assertions {
+        let actual = actual_value(location, response, assertion.subject)?;
+        if !is_satisfied(actual.as_deref(), assertion.operator, assertion.expected) {
+            return Err(Error::Assertion {
+                location: location.clone(),
+                line: assertion.line,
+                expression: assertion.expression(),
+                actual: actual.unwrap_or_else(|| "none".to_owned()),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// check assertions with body of response, and response is rebuilt with the read body for caller
+pub async fn check_response(location: &RequestLocation, response: Response, assertions: &[Assertion<'_>]) -> Result<Response> {
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+    let bytes = response.bytes().await.map_err(|error| Error::Transport { location: location.clone(), error })?;
+    let captured = CapturedResponse { status: status.as_u16(), headers: headers.clone(), body: String::from_utf8_lossy(&bytes).to_string() };
+    check(location, &captured, assertions)?;
+    let mut builder = http::Response::builder().status(status).version(version);
+    if let Some(builder_headers) = builder.headers_mut() {
+        *builder_headers = headers;
+    }
+    Ok(Response::from(builder.body(bytes).unwrap()))
+}
+
+fn actual_value(location: &RequestLocation, response: &CapturedResponse, subject: &str) -> Result<Option<String>> {
+    if let Some(name) = subject.strip_prefix("header ") {
+        return response.get(location, &format!("headers.{}", name.trim()));
+    }
+    // length of array, string or object, and count of selected values otherwise
+    if let Some(path) = subject.strip_prefix('$').and_then(|_| subject.strip_suffix(".length")) {
+        let json: Value = serde_json::from_str(&response.body)
+            .map_err(|e| Error::Extract { location: location.clone(), path: path.to_string(), message: format!("illegal json: {}", e) })?;
+        let length = match extract::json_path(location, &json, path)?.as_slice() {
+            [Value::Array(items)] => items.len(),
+            [Value::String(text)] => text.chars().count(),
+            [Value::Object(fields)] => fields.len(),
+            values => values.len(),
+        };
+        return Ok(Some(length.to_string()));
+    }
+    match subject {
+        "status" | "body" => response.get(location, subject),
+        path => response.get(location, &format!("body.{}", path)),
+    }
+}
+
+/// numbers are compared by value, and other values are compared as text
+fn is_satisfied(actual: Option<&str>, operator: &str, expected: &str) -> bool {
+    let Some(actual) = actual else {
+        return false;
+    };
+    let ordering = match (actual.parse::<f64>(), expected.parse::<f64>()) {
+        (Ok(actual), Ok(expected)) => actual.partial_cmp(&expected),
+        _ => None,
+    };
+    match operator {
+        "exists" => true,
+        "==" => ordering.map_or(actual == expected, Ordering::is_eq),
+        "!=" => ordering.map_or(actual != expected, Ordering::is_ne),
+        ">" => ordering.is_some_and(Ordering::is_gt),
+        ">=" => ordering.is_some_and(Ordering::is_ge),
+        "<" => ordering.is_some_and(Ordering::is_lt),
+        "<=" => ordering.is_some_and(Ordering::is_le),
+        "contains" => actual.contains(expected),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderMap;
+
+    const LOCATION: RequestLocation = RequestLocation::new("list-items", "index.http", 3);
+
+    fn captured_response() -> CapturedResponse {
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Type", "application/json".parse().unwrap());
+        CapturedResponse { status: 200, headers, body: r#"{"items": [{"id": 1}, {"id": 2}], "name": "demo", "paid": true}"#.to_owned() }
+    }
+
+    #[test]
+    fn test_check() {
+        let assertions = [
+            Assertion::new(5, "status", "==", "200"),
+            Assertion::new(6, "header Content-Type", "contains", "json"),
+            Assertion::new(7, "$.items.length", ">", "0"),
+            Assertion::new(8, "$.items[1].id", ">=", "2"),
+            Assertion::new(9, "$.name", "==", "demo"),
+            Assertion::new(10, "$.paid", "!=", "false"),
+            Assertion::new(11, "$.name", "exists", ""),
+        ];
+        check(&LOCATION, &captured_response(), &assertions).unwrap();
+        let error = check(&LOCATION, &captured_response(), &[Assertion::new(12, "$.items.length", "==", "3")]).unwrap_err();
+        println!("{}", error);
+        assert!(matches!(&error, Error::Assertion { line: 12, actual, .. } if actual == "2"));
+        assert!(error.to_string().contains("index.http:12"));
+        let error = check(&LOCATION, &captured_response(), &[Assertion::new(13, "$.missing", "exists", "")]).unwrap_err();
+        assert!(matches!(error, Error::Assertion { actual, .. } if actual == "none"));
+    }
+
+    #[tokio::test]
+    async fn test_check_response() {
+        let http_response = http::Response::builder().status(201).header("X-Id", "1").body("created").unwrap();
+        let assertions = [Assertion::new(5, "status", "==", "201"), Assertion::new(6, "body", "contains", "create")];
+        let response = check_response(&LOCATION, Response::from(http_response), &assertions).await.unwrap();
+        assert_eq!(response.status().as_u16(), 201);
+        assert_eq!(response.headers()["X-Id"], "1");
+        assert_eq!(response.text().await.unwrap(), "created");
+    }
+}
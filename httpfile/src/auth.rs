@@ -0,0 +1,102 @@
+use base64::Engine;
+use reqwest::header::{HeaderValue, AUTHORIZATION, WWW_AUTHENTICATE};
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+/// encode IDE form `Basic user password` or `Basic user:password` into `Basic base64(user:password)`,
+/// and encoded credentials or other schemes are kept as they are
+pub fn encode_basic_auth(value: &str) -> String {
+    let Some(credentials) = value.strip_prefix("Basic ") else {
+        return value.to_string();
+    };
+    let credentials = credentials.trim();
+    let user_password = match credentials.split_once(' ') {
+        Some((user, password)) => format!("{}:{}", user, password.trim()),
+        None if credentials.contains(':') => credentials.to_string(),
+        None => return value.to_string(),
+    };
+    format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(user_password))
+}
+
+/// user and password from IDE form `Digest user password`
+pub fn parse_digest_auth(value: &str) -> Option<(String, String)> {
+    let credentials = value.strip_prefix("Digest ")?.trim();
+    let (user, password) = credentials.split_once(' ').unwrap_or((credentials, ""));
+    Some((user.to_string(), password.trim().to_string()))
+}
+
+/// send request, and answer digest challenge from `WWW-Authenticate` with credentials if 401 returned
+pub async fn send_with_digest_auth(request_builder: RequestBuilder, user: &str, password: &str) -> reqwest::Result<Response> {
+    let (client, request) = request_builder.build_split();
+    let request = request?;
+    let retry_request = request.try_clone();
+    let response = client.execute(request).await?;
+    let Some(mut retry_request) = retry_request else {
+        return Ok(response);
+    };
+    if response.status() != StatusCode::UNAUTHORIZED {
+        return Ok(response);
+    }
+    let challenge = response.headers().get(WWW_AUTHENTICATE).and_then(|value| value.to_str().ok()).unwrap_or_default();
+    let Ok(mut prompt) = digest_auth::parse(challenge) else {
+        return Ok(response);
+    };
+    let url = retry_request.url();
+    let uri = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+    let body = retry_request.body().and_then(|body| body.as_bytes()).map(|body| body.to_vec());
+    let method = retry_request.method().as_str().to_string();
+    let context = digest_auth::AuthContext::new_with_method(user, password, uri, body, digest_auth::HttpMethod::from(method.as_str()));
+    let Ok(answer) = prompt.respond(&context) else {
+        return Ok(response);
+    };
+    if let Ok(header_value) = HeaderValue::from_str(&answer.to_header_string()) {
+        retry_request.headers_mut().insert(AUTHORIZATION, header_value);
+    }
+    client.execute(retry_request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_encode_basic_auth() {
+        assert_eq!(encode_basic_auth("Basic admin secret"), "Basic YWRtaW46c2VjcmV0");
+        assert_eq!(encode_basic_auth("Basic admin:secret"), "Basic YWRtaW46c2VjcmV0");
+        assert_eq!(encode_basic_auth("Basic YWRtaW46c2VjcmV0"), "Basic YWRtaW46c2VjcmV0");
+        assert_eq!(encode_basic_auth("Bearer token"), "Bearer token");
+    }
+
+    #[test]
+    fn test_parse_digest_auth() {
+        assert_eq!(parse_digest_auth("Digest admin secret"), Some(("admin".to_string(), "secret".to_string())));
+        assert_eq!(parse_digest_auth("Basic admin secret"), None);
+    }
+
+    #[tokio::test]
+    async fn test_send_with_digest_auth() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0u8; 4096];
+                let size = stream.read(&mut buffer).await.unwrap();
+                let request = String::from_utf8_lossy(&buffer[..size]).to_lowercase();
+                let response = if request.contains("authorization: digest username=\"admin\"") {
+                    "HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok".to_string()
+                } else {
+                    "HTTP/1.1 401 Unauthorized\r\nwww-authenticate: Digest realm=\"demo\", qop=\"auth\", nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\", opaque=\"5ccc069c403ebaf9f0171e9517f40e41\"\r\ncontent-length: 0\r\nconnection: close\r\n\r\n".to_string()
+                };
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        let request_builder = reqwest::Client::new().get(format!("http://{}/digest-auth", address));
+        let response = send_with_digest_auth(request_builder, "admin", "secret").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt::Display;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use futures_util::Stream;
+use reqwest::header::HeaderMap;
+use reqwest::Url;
+use tokio::sync::mpsc;
+use crate::{Error, RequestLocation, Result};
+
+#[cfg(feature = "amqp")]
+mod amqp;
+#[cfg(feature = "kafka")]
+mod kafka;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+#[cfg(feature = "nats")]
+mod nats;
+#[cfg(feature = "redis")]
+mod redis;
+
+/// URI schemes of brokers and cargo features of httpfile to enable them
+const BROKER_FEATURES: [(&str, &str); 5] = [("mqtt", "mqtt"), ("nats", "nats"), ("kafka", "kafka"), ("redis", "redis"), ("amqp", "amqp")];
+
+/// message received from broker subscription
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokerMessage {
+    pub topic: String,
+    pub payload: Vec<u8>,
+}
+
+impl BrokerMessage {
+    /// payload decoded as UTF-8 lossily
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.payload).to_string()
+    }
+}
+
+/// messages of `SUB` request, and subscription is closed after stream dropped
+pub struct MessageStream {
+    receiver: mpsc::Receiver<Result<BrokerMessage>>,
+}
+
+impl MessageStream {
+    /// next message, and `None` after subscription closed by broker
+    pub async fn next_message(&mut self) -> Option<Result<BrokerMessage>> {
+        self.receiver.recv().await
+    }
+}
+
+impl Stream for MessageStream {
+    type Item = Result<BrokerMessage>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// publish body to topic of broker by URI scheme, such as `mqtt://localhost:1883` or `nats://localhost:4222`
+#[allow(unused_variables)]
+pub async fn publish(location: &RequestLocation, uri: &str, topic: &str, headers: HeaderMap, body: &str) -> Result<()> {
+    let url = parse_uri(location, uri)?;
+    match url.scheme() {
+        #[cfg(feature = "mqtt")]
+        "mqtt" => mqtt::publish(location, &url, topic, body).await,
+        #[cfg(feature = "nats")]
+        "nats" => nats::publish(location, &url, topic, &headers, body).await,
+        #[cfg(feature = "kafka")]
+        "kafka" => kafka::publish(location, &url, topic, &headers, body).await,
+        #[cfg(feature = "redis")]
+        "redis" => redis::publish(location, &url, topic, body).await,
+        #[cfg(feature = "amqp")]
+        "amqp" => amqp::publish(location, &url, topic, &headers, body).await,
+        scheme => Err(unsupported_broker(location, scheme)),
+    }
+}
+
+/// subscribe topic of broker by URI scheme, and messages are received in background
+#[allow(unused_variables)]
+pub async fn subscribe(location: &RequestLocation, uri: &str, topic: &str) -> Result<MessageStream> {
+    let url = parse_uri(location, uri)?;
+    let (sender, receiver) = mpsc::channel(16);
+    let subscribed: Result<()> = match url.scheme() {
+        #[cfg(feature = "mqtt")]
+        "mqtt" => mqtt::subscribe(location, &url, topic, sender).await,
+        #[cfg(feature = "nats")]
+        "nats" => nats::subscribe(location, &url, topic, sender).await,
+        #[cfg(feature = "kafka")]
+        "kafka" => kafka::subscribe(location, &url, topic, sender).await,
+        #[cfg(feature = "redis")]
+        "redis" => redis::subscribe(location, &url, topic, sender).await,
+        #[cfg(feature = "amqp")]
+        "amqp" => amqp::subscribe(location, &url, topic, sender).await,
+        scheme => Err(unsupported_broker(location, scheme)),
+    };
+    subscribed.map(|_| MessageStream { receiver })
+}
+
+fn parse_uri(location: &RequestLocation, uri: &str) -> Result<Url> {
+    Url::parse(uri).map_err(|e| to_error(location, format!("illegal broker uri {:?}: {}", uri, e)))
+}
+
+fn unsupported_broker(location: &RequestLocation, scheme: &str) -> Error {
+    match BROKER_FEATURES.iter().find(|(broker_scheme, _)| *broker_scheme == scheme) {
+        Some((_, feature)) => to_error(location, format!("`{}://` requires `{}` feature of httpfile", scheme, feature)),
+        None => to_error(location, format!("unsupported broker: {}", scheme)),
+    }
+}
+
+pub(crate) fn to_error(location: &RequestLocation, message: impl Display) -> Error {
+    Error::Broker { location: location.clone(), message: message.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCATION: RequestLocation = RequestLocation::new("publish", "index.http", 3);
+
+    #[tokio::test]
+    async fn test_unsupported_broker() {
+        let error = publish(&LOCATION, "ftp://localhost/", "topic", HeaderMap::new(), "hello").await.unwrap_err();
+        println!("{}", error);
+        assert!(matches!(error, Error::Broker { message, .. } if message == "unsupported broker: ftp"));
+        assert!(matches!(subscribe(&LOCATION, "localhost:1883", "topic").await, Err(Error::Broker { .. })));
+    }
+
+    #[cfg(not(feature = "mqtt"))]
+    #[tokio::test]
+    async fn test_broker_feature_required() {
+        let error = publish(&LOCATION, "mqtt://localhost:1883", "topic", HeaderMap::new(), "hello").await.unwrap_err();
+        assert!(error.to_string().contains("`mqtt://` requires `mqtt` feature of httpfile"));
+    }
+}
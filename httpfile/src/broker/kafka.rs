@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use reqwest::header::HeaderMap;
+use reqwest::Url;
+use rskafka::client::partition::{Compression, OffsetAt, PartitionClient, UnknownTopicHandling};
+use rskafka::client::ClientBuilder;
+use rskafka::chrono::DateTime;
+use rskafka::record::Record;
+use tokio::sync::mpsc;
+use crate::broker::{to_error, BrokerMessage};
+use crate::{RequestLocation, Result};
+
+/// client of topic partition, and partition is from URI query, such as `kafka://localhost:9092?partition=1`
+async fn partition_client(location: &RequestLocation, url: &Url, topic: &str) -> Result<PartitionClient> {
+    let bootstrap_broker = format!("{}:{}", url.host_str().unwrap_or("localhost"), url.port().unwrap_or(9092));
+    let client = ClientBuilder::new(vec![bootstrap_broker]).build().await.map_err(|e| to_error(location, e))?;
+    let partition = url.query_pairs().find(|(name, _)| name == "partition").and_then(|(_, value)| value.parse().ok()).unwrap_or(0);
+    client.partition_client(topic, partition, UnknownTopicHandling::Error).await.map_err(|e| to_error(location, e))
+}
+
+/// produce record with headers, and returned after acknowledged by broker
+pub(super) async fn publish(location: &RequestLocation, url: &Url, topic: &str, headers: &HeaderMap, body: &str) -> Result<()> {
+    let partition_client = partition_client(location, url, topic).await?;
+    let now_millis = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64;
+    let record = Record {
+        key: None,
+        value: Some(body.as_bytes().to_vec()),
+        headers: headers.iter().map(|(name, value)| (name.to_string(), value.as_bytes().to_vec())).collect::<BTreeMap<String, Vec<u8>>>(),
+        timestamp: DateTime::from_timestamp_millis(now_millis).unwrap_or_default(),
+    };
+    partition_client.produce(vec![record], Compression::NoCompression).await.map_err(|e| to_error(location, e))?;
+    Ok(())
+}
+
+/// fetch records from latest offset of partition
+pub(super) async fn subscribe(location: &RequestLocation, url: &Url, topic: &str, sender: mpsc::Sender<Result<BrokerMessage>>) -> Result<()> {
+    let partition_client = partition_client(location, url, topic).await?;
+    let mut offset = partition_client.get_offset(OffsetAt::Latest).await.map_err(|e| to_error(location, e))?;
+    let topic = topic.to_string();
+    let location = location.clone();
+    tokio::spawn(async move {
+        loop {
+            let fetched = tokio::select! {
+                fetched = partition_client.fetch_records(offset, 1..1_000_000, 1_000) => fetched,
+                _ = sender.closed() => return,
+            };
+            let records = match fetched {
+                Ok((records, _)) => records,
+                Err(e) => {
+                    let _ = sender.send(Err(to_error(&location, e))).await;
+                    return;
+                }
+            };
+            for record_and_offset in records {
+                offset = record_and_offset.offset + 1;
+                let message = BrokerMessage { topic: topic.clone(), payload: record_and_offset.record.value.unwrap_or_default() };
+                if sender.send(Ok(message)).await.is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCATION: RequestLocation = RequestLocation::new("kafka", "index.http", 3);
+
+    /// requires local Kafka broker with topic `httpfile-test`: `cargo test --features kafka -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn test_publish_and_subscribe() {
+        let url = Url::parse(&std::env::var("KAFKA_URI").unwrap_or_else(|_| "kafka://127.0.0.1:9092".to_string())).unwrap();
+        let (sender, mut receiver) = mpsc::channel(4);
+        subscribe(&LOCATION, &url, "httpfile-test", sender).await.unwrap();
+        publish(&LOCATION, &url, "httpfile-test", &HeaderMap::new(), "hello kafka").await.unwrap();
+        let message = receiver.recv().await.unwrap().unwrap();
+        assert_eq!(message.text(), "hello kafka");
+    }
+}
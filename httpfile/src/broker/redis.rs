@@ -0,0 +1,98 @@
+use futures_util::StreamExt;
+use reqwest::Url;
+use tokio::sync::mpsc;
+use crate::broker::{to_error, BrokerMessage};
+use crate::{RequestLocation, Result};
+
+/// publish to channel of Redis Pub/Sub
+pub(super) async fn publish(location: &RequestLocation, url: &Url, topic: &str, body: &str) -> Result<()> {
+    let client = redis::Client::open(url.as_str()).map_err(|e| to_error(location, e))?;
+    let mut connection = client.get_multiplexed_async_connection().await.map_err(|e| to_error(location, e))?;
+    redis::cmd("PUBLISH").arg(topic).arg(body).query_async::<i64>(&mut connection).await.map_err(|e| to_error(location, e))?;
+    Ok(())
+}
+
+pub(super) async fn subscribe(location: &RequestLocation, url: &Url, topic: &str, sender: mpsc::Sender<Result<BrokerMessage>>) -> Result<()> {
+    let client = redis::Client::open(url.as_str()).map_err(|e| to_error(location, e))?;
+    let mut pubsub = client.get_async_pubsub().await.map_err(|e| to_error(location, e))?;
+    pubsub.subscribe(topic).await.map_err(|e| to_error(location, e))?;
+    tokio::spawn(async move {
+        let mut messages = pubsub.into_on_message();
+        loop {
+            let message = tokio::select! {
+                message = messages.next() => message,
+                _ = sender.closed() => return,
+            };
+            let Some(message) = message else { return };
+            let message = BrokerMessage { topic: message.get_channel_name().to_string(), payload: message.get_payload_bytes().to_vec() };
+            if sender.send(Ok(message)).await.is_err() {
+                return;
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    const LOCATION: RequestLocation = RequestLocation::new("redis", "index.http", 3);
+
+    fn to_bulk_string(text: &str) -> String {
+        format!("${}\r\n{}\r\n", text.len(), text)
+    }
+
+    /// mock server with RESP2: reports published messages, and sends `hello` message after subscribed
+    async fn start_redis_server(published: mpsc::Sender<String>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let published = published.clone();
+                tokio::spawn(async move {
+                    let (reader, mut writer) = stream.into_split();
+                    let mut lines = BufReader::new(reader).lines();
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        // command array: `*N` then `$length` and argument lines
+                        let Some(count) = line.strip_prefix('*').and_then(|count| count.parse::<usize>().ok()) else { continue };
+                        let mut args: Vec<String> = Vec::new();
+                        for _ in 0..count {
+                            lines.next_line().await.unwrap();
+                            args.push(lines.next_line().await.unwrap().unwrap());
+                        }
+                        let reply = match args[0].to_uppercase().as_str() {
+                            "PUBLISH" => {
+                                published.send(format!("{} {}", args[1], args[2])).await.unwrap();
+                                ":1\r\n".to_owned()
+                            }
+                            "SUBSCRIBE" => {
+                                let subscribed = format!("*3\r\n{}{}:1\r\n", to_bulk_string("subscribe"), to_bulk_string(&args[1]));
+                                let message = format!("*3\r\n{}{}{}", to_bulk_string("message"), to_bulk_string(&args[1]), to_bulk_string("hello"));
+                                format!("{}{}", subscribed, message)
+                            }
+                            _ => "+OK\r\n".to_owned(),
+                        };
+                        writer.write_all(reply.as_bytes()).await.unwrap();
+                    }
+                });
+            }
+        });
+        format!("redis://{}", address)
+    }
+
+    #[tokio::test]
+    async fn test_publish_and_subscribe() {
+        let (published_sender, mut published_receiver) = mpsc::channel(4);
+        let url = Url::parse(&start_redis_server(published_sender).await).unwrap();
+        publish(&LOCATION, &url, "greeting", "hello redis").await.unwrap();
+        assert_eq!(published_receiver.recv().await.unwrap(), "greeting hello redis");
+        let (sender, mut receiver) = mpsc::channel(4);
+        subscribe(&LOCATION, &url, "news", sender).await.unwrap();
+        let message = receiver.recv().await.unwrap().unwrap();
+        assert_eq!(message, BrokerMessage { topic: "news".to_string(), payload: b"hello".to_vec() });
+    }
+}
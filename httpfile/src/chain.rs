@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::future::Future;
+use reqwest::header::HeaderMap;
+use reqwest::Response;
+use crate::extract;
+use crate::shell::CommandOutput;
+use crate::{Error, RequestLocation, Result};
+
+pub use crate::oauth2::merge_params;
+
+/// response of named request kept for `{{name.response.body.$.token}}` and `{{name.response.headers.Location}}` references
+#[derive(Debug, Clone, Default)]
+pub struct CapturedResponse {
+    /// http status, exit status of `SHELL` and `SSH` requests, or 200 for messages of `SUB` and json output of `GRPC` requests
+    pub status: u16,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+impl CapturedResponse {
+    /// value for path after `response.`: `status`, `body`, `body.<jsonpath>`, `body.<xpath>` or `headers.<Name>`
+    pub fn get(&self, location: &RequestLocation, path: &str) -> Result<Option<String>> {
+        match path {
+            "status" => Ok(Some(self.status.to_string())),
+            "body" => Ok(Some(self.body.clone())),
+            _ => {
+                if let Some(body_path) = path.strip_prefix("body.") {
+                    extract::extract(location, &self.body, body_path)
+                } else if let Some(name) = path.strip_prefix("headers.") {
+                    Ok(self.headers.get(name).and_then(|value| value.to_str().ok()).map(|value| value.to_string()))
+                } else {
+                    Ok(None)
+                }
+            }
+        }
+    }
+}
+
+impl From<CommandOutput> for CapturedResponse {
+    fn from(output: CommandOutput) -> Self {
+        CapturedResponse { status: output.status as u16, headers: HeaderMap::new(), body: output.stdout }
+    }
+}
+
+/// json output of `GRPC` request alike response body
+impl From<serde_json::Value> for CapturedResponse {
+    fn from(json: serde_json::Value) -> Self {
+        CapturedResponse { status: 200, headers: HeaderMap::new(), body: json.to_string() }
+    }
+}
+
+/// output of generated function which can be referenced by later requests
+pub trait Capture {
+    fn capture(self, location: &RequestLocation) -> impl Future<Output = Result<CapturedResponse>> + Send;
+}
+
+impl Capture for Response {
+    async fn capture(self, location: &RequestLocation) -> Result<CapturedResponse> {
+        let status = self.status().as_u16();
+        let headers = self.headers().clone();
+        let body = self.text().await.map_err(|error| Error::Transport { location: location.clone(), error })?;
+        Ok(CapturedResponse { status, headers, body })
+    }
+}
+
+impl Capture for CommandOutput {
+    async fn capture(self, _location: &RequestLocation) -> Result<CapturedResponse> {
+        Ok(CapturedResponse::from(self))
+    }
+}
+
+/// capture response of dependency request for generated code
+pub async fn capture<T: Capture>(location: &RequestLocation, output: T) -> Result<CapturedResponse> {
+    output.capture(location).await
+}
+
+/// resolve references, such as `login.response.body.$.token`, into params, and `MissingVariable` error if value not found
+pub fn resolve(location: &RequestLocation, references: &[(&str, &CapturedResponse)]) -> Result<HashMap<String, String>> {
+    let mut values: HashMap<String, String> = HashMap::new();
+    for (variable, response) in references {
+        let value = match variable.split_once(".response.") {
+            Some((_, path)) => response.get(location, path)?,
+            None => None,
+        };
+        match value {
+            Some(value) => values.insert(variable.to_string(), value),
+            None => return Err(Error::MissingVariable { location: location.clone(), name: variable.to_string() }),
+        };
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCATION: RequestLocation = RequestLocation::new("profile", "index.http", 8);
+
+    #[test]
+    fn test_resolve() {
+        let mut headers = HeaderMap::new();
+        headers.insert("Location", "/users/1".parse().unwrap());
+        let login = CapturedResponse { status: 200, headers, body: r#"{"token":"abc","roles":[{"name":"admin"}],"expires":3600}"#.to_owned() };
+        let references = [
+            ("login.response.body.$.token", &login),
+            ("login.response.body.$.roles[0].name", &login),
+            ("login.response.body.$['expires']", &login),
+            ("login.response.headers.Location", &login),
+            ("login.response.status", &login),
+        ];
+        let values = resolve(&LOCATION, &references).unwrap();
+        println!("{:?}", values);
+        assert_eq!(values["login.response.body.$.token"], "abc");
+        assert_eq!(values["login.response.body.$.roles[0].name"], "admin");
+        assert_eq!(values["login.response.body.$['expires']"], "3600");
+        assert_eq!(values["login.response.headers.Location"], "/users/1");
+        assert_eq!(values["login.response.status"], "200");
+        let error = resolve(&LOCATION, &[("login.response.body.$.missing", &login)]).unwrap_err();
+        assert!(matches!(error, Error::MissingVariable { name, .. } if name == "login.response.body.$.missing"));
+    }
+
+    #[cfg(feature = "xml")]
+    #[test]
+    fn test_resolve_xpath() {
+        let logout = CapturedResponse { status: 200, headers: HeaderMap::new(), body: "<result><code>0</code></result>".to_owned() };
+        let values = resolve(&LOCATION, &[("logout.response.body./result/code", &logout)]).unwrap();
+        assert_eq!(values["logout.response.body./result/code"], "0");
+    }
+}
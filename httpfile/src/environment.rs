@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use serde_json::Value;
+
+pub const ENV_FILE_NAME: &str = "http-client.env.json";
+pub const PRIVATE_ENV_FILE_NAME: &str = "http-client.private.env.json";
+
+/// load environment json files in the directory, and private env file overrides shared env file
+pub(crate) fn load_env_files(env_dir: &Path) -> std::io::Result<Vec<Value>> {
+    let mut env_jsons = Vec::new();
+    for file_name in [ENV_FILE_NAME, PRIVATE_ENV_FILE_NAME] {
+        let env_file = env_dir.join(file_name);
+        if env_file.exists() {
+            env_jsons.push(serde_json::from_str(&std::fs::read_to_string(env_file)?)?);
+        }
+    }
+    Ok(env_jsons)
+}
+
+/// environment names declared in `http-client.env.json` and `http-client.private.env.json`
+pub fn list_environments(env_dir: &Path) -> std::io::Result<Vec<String>> {
+    let mut names: Vec<String> = Vec::new();
+    for env_json in load_env_files(env_dir)? {
+        if let Value::Object(envs) = env_json {
+            for name in envs.keys() {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+    }
+    Ok(names)
+}
+
+/// load variables of the environment, and nested objects, such as `SSLConfiguration`, are skipped
+pub fn load_environment(env_dir: &Path, env_name: &str) -> std::io::Result<HashMap<String, String>> {
+    let mut found = false;
+    let mut variables = HashMap::new();
+    for env_json in load_env_files(env_dir)? {
+        if let Some(Value::Object(env)) = env_json.get(env_name) {
+            found = true;
+            for (name, value) in env {
+                let value = match value {
+                    Value::String(text) => text.clone(),
+                    Value::Object(_) | Value::Array(_) => continue,
+                    other => other.to_string(),
+                };
+                variables.insert(name.clone(), value);
+            }
+        }
+    }
+    if !found {
+        return Err(Error::new(ErrorKind::NotFound, format!("environment not found: {}", env_name)));
+    }
+    Ok(variables)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_environment() {
+        let env_dir = Path::new("..");
+        assert_eq!(list_environments(env_dir).unwrap(), vec!["dev".to_string()]);
+        let variables = load_environment(env_dir, "dev").unwrap();
+        assert_eq!(variables.get("host").unwrap(), "httpbin.org");
+        assert!(load_environment(env_dir, "prod").is_err());
+    }
+}
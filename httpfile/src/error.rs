@@ -0,0 +1,136 @@
+use std::borrow::Cow;
+use std::fmt;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// request name and source line in http file for diagnostics, and functions take it by reference
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestLocation {
+    pub name: Cow<'static, str>,
+    pub file: Cow<'static, str>,
+    pub line: u32,
+}
+
+impl RequestLocation {
+    /// location of generated function, and borrowed names keep it const
+    pub const fn new(name: &'static str, file: &'static str, line: u32) -> RequestLocation {
+        RequestLocation { name: Cow::Borrowed(name), file: Cow::Borrowed(file), line }
+    }
+
+    /// location with owned names, such as requests loaded by `httpfile run` at runtime
+    pub fn owned(name: impl Into<String>, file: impl Into<String>, line: u32) -> RequestLocation {
+        RequestLocation { name: Cow::Owned(name.into()), file: Cow::Owned(file.into()), line }
+    }
+}
+
+impl fmt::Display for RequestLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}:{})", self.name, self.file, self.line)
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// illegal header name or value after variables rendered
+    InvalidHeader { location: RequestLocation, name: String, message: String },
+    /// failed to render body template
+    Template { location: RequestLocation, message: String },
+    /// variable not found in params
+    MissingVariable { location: RequestLocation, name: String },
+    /// failed to obtain OAuth2 token for `$auth.token("name")`
+    Auth { location: RequestLocation, name: String, message: String },
+    /// illegal proxy url from `@proxy` tag
+    Proxy { location: RequestLocation, proxy: String, message: String },
+    /// failed to connect WebSocket server or exchange messages
+    WebSocket { location: RequestLocation, message: String },
+    /// gRPC call failed with status code, such as `NotFound` or `Unavailable`
+    Grpc { location: RequestLocation, code: String, message: String },
+    /// JSON-RPC response with `error` object, and `data` is kept as json text
+    JsonRpc { location: RequestLocation, code: i64, message: String, data: Option<String> },
+    /// failed to publish or subscribe message broker for `PUB` and `SUB` requests
+    Broker { location: RequestLocation, message: String },
+    /// failed to run script of `SHELL` or `SSH` request, or script exited with non-zero status
+    Shell { location: RequestLocation, status: Option<i32>, message: String },
+    /// illegal JSONPath or XPath expression, or body is not json or xml
+    Extract { location: RequestLocation, path: String, message: String },
+    /// `> assert` line failed, and line in http file and actual value are kept for diagnostics
+    Assertion { location: RequestLocation, line: u32, expression: String, actual: String },
+    /// failed to send request or read response
+    Transport { location: RequestLocation, error: reqwest::Error },
+    /// unexpected response status, and body is kept for diagnostics
+    Status { location: RequestLocation, status: u16, body: String },
+    /// failed to decode response body, and status with body are kept for diagnostics
+    Decode { location: RequestLocation, status: u16, body: String, message: String },
+}
+
+impl Error {
+    pub fn location(&self) -> &RequestLocation {
+        match self {
+            Error::InvalidHeader { location, .. }
+            | Error::Template { location, .. }
+            | Error::MissingVariable { location, .. }
+            | Error::Auth { location, .. }
+            | Error::Proxy { location, .. }
+            | Error::WebSocket { location, .. }
+            | Error::Grpc { location, .. }
+            | Error::JsonRpc { location, .. }
+            | Error::Broker { location, .. }
+            | Error::Shell { location, .. }
+            | Error::Extract { location, .. }
+            | Error::Assertion { location, .. }
+            | Error::Transport { location, .. }
+            | Error::Status { location, .. }
+            | Error::Decode { location, .. } => location,
+        }
+    }
+
+    /// response status if response received
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::Status { status, .. } | Error::Decode { status, .. } => Some(*status),
+            Error::Transport { error, .. } => error.status().map(|status| status.as_u16()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidHeader { location, name, message } => write!(f, "{}: invalid header {}: {}", location, name, message),
+            Error::Template { location, message } => write!(f, "{}: failed to render body: {}", location, message),
+            Error::MissingVariable { location, name } => write!(f, "{}: variable not found: {}", location, name),
+            Error::Auth { location, name, message } => write!(f, "{}: failed to obtain token for auth {}: {}", location, name, message),
+            Error::Proxy { location, proxy, message } => write!(f, "{}: invalid proxy {}: {}", location, proxy, message),
+            Error::WebSocket { location, message } => write!(f, "{}: websocket error: {}", location, message),
+            Error::Grpc { location, code, message } => write!(f, "{}: grpc error {}: {}", location, code, message),
+            Error::JsonRpc { location, code, message, data } => match data {
+                Some(data) => write!(f, "{}: json-rpc error {}: {}, data: {}", location, code, message, data),
+                None => write!(f, "{}: json-rpc error {}: {}", location, code, message),
+            },
+            Error::Broker { location, message } => write!(f, "{}: broker error: {}", location, message),
+            Error::Shell { location, status, message } => match status {
+                Some(status) => write!(f, "{}: command exited with status {}: {}", location, status, message),
+                None => write!(f, "{}: command failed: {}", location, message),
+            },
+            Error::Extract { location, path, message } => write!(f, "{}: failed to extract {}: {}", location, path, message),
+            Error::Assertion { location, line, expression, actual } => {
+                write!(f, "{}: assertion failed at {}:{}: {}, actual: {}", location, location.file, line, expression, actual)
+            }
+            Error::Transport { location, error } => write!(f, "{}: transport error: {}", location, error),
+            Error::Status { location, status, body } => write!(f, "{}: unexpected status {}, body: {}", location, status, body),
+            Error::Decode { location, status, body, message } => {
+                write!(f, "{}: failed to decode response with status {}: {}, body: {}", location, status, message, body)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Transport { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
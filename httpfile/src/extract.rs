@@ -0,0 +1,123 @@
+use std::future::Future;
+use reqwest::Response;
+use serde_json::Value;
+use serde_json_path::JsonPath;
+#[cfg(feature = "xml")]
+use sxd_xpath::{Context, Factory};
+use crate::{Error, RequestLocation, Result};
+
+/// values selected by JSONPath, such as `$.data[0].id` or `$.items[?@.price < 10].name`
+pub fn json_path(location: &RequestLocation, json: &Value, path: &str) -> Result<Vec<Value>> {
+    let json_path = JsonPath::parse(path).map_err(|e| to_error(location, path, e))?;
+    Ok(json_path.query(json).all().into_iter().cloned().collect())
+}
+
+/// string values selected by XPath, such as `//user/name` or `count(//user)`
+#[cfg(feature = "xml")]
+pub fn xpath(location: &RequestLocation, xml: &str, path: &str) -> Result<Vec<String>> {
+    let package = sxd_document::parser::parse(xml).map_err(|e| to_error(location, path, format!("illegal xml: {}", e)))?;
+    let document = package.as_document();
+    let xpath = Factory::new()
+        .build(path)
+        .map_err(|e| to_error(location, path, e))?
+        .ok_or_else(|| to_error(location, path, "empty xpath"))?;
+    let value = xpath.evaluate(&Context::new(), document.root()).map_err(|e| to_error(location, path, e))?;
+    Ok(match value {
+        sxd_xpath::Value::Nodeset(nodes) => nodes.document_order().iter().map(|node| node.string_value()).collect(),
+        sxd_xpath::Value::String(text) => vec![text],
+        sxd_xpath::Value::Number(number) if number.fract() == 0.0 => vec![format!("{}", number as i64)],
+        sxd_xpath::Value::Number(number) => vec![number.to_string()],
+        sxd_xpath::Value::Boolean(boolean) => vec![boolean.to_string()],
+    })
+}
+
+/// string values selected by XPath, and `xml` feature of httpfile is required
+#[cfg(not(feature = "xml"))]
+pub fn xpath(location: &RequestLocation, _xml: &str, path: &str) -> Result<Vec<String>> {
+    Err(to_error(location, path, "XPath requires `xml` feature of httpfile"))
+}
+
+/// first value in body as text, and path is JSONPath if starts with `$`, otherwise XPath, such as `/user/id`
+pub fn extract(location: &RequestLocation, body: &str, path: &str) -> Result<Option<String>> {
+    if path.starts_with('$') {
+        let json: Value = serde_json::from_str(body).map_err(|e| to_error(location, path, format!("illegal json: {}", e)))?;
+        Ok(json_path(location, &json, path)?.first().map(to_text))
+    } else {
+        Ok(xpath(location, body, path)?.into_iter().next())
+    }
+}
+
+/// text of json value, and strings are not quoted
+pub fn to_text(json: &Value) -> String {
+    match json {
+        Value::String(text) => text.clone(),
+        json => json.to_string(),
+    }
+}
+
+/// JSONPath and XPath extraction on response body, and body is consumed
+pub trait ResponseExt {
+    /// values selected by JSONPath from json body
+    fn json_path(self, location: &RequestLocation, path: &str) -> impl Future<Output = Result<Vec<Value>>> + Send;
+    /// string values selected by XPath from xml body
+    fn xpath(self, location: &RequestLocation, path: &str) -> impl Future<Output = Result<Vec<String>>> + Send;
+}
+
+impl ResponseExt for Response {
+    async fn json_path(self, location: &RequestLocation, path: &str) -> Result<Vec<Value>> {
+        let json: Value = crate::decode_json(location, self).await?;
+        json_path(location, &json, path)
+    }
+
+    async fn xpath(self, location: &RequestLocation, path: &str) -> Result<Vec<String>> {
+        let text = crate::decode_text(location, self).await?;
+        xpath(location, &text, path)
+    }
+}
+
+fn to_error(location: &RequestLocation, path: &str, message: impl std::fmt::Display) -> Error {
+    Error::Extract { location: location.clone(), path: path.to_string(), message: message.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const LOCATION: RequestLocation = RequestLocation::new("users", "index.http", 3);
+
+    #[test]
+    fn test_json_path() {
+        let json = json!({"data": [{"id": 1, "name": "linux_china"}, {"id": 2, "name": "jackie"}]});
+        assert_eq!(json_path(&LOCATION, &json, "$.data[0].id").unwrap(), vec![json!(1)]);
+        assert_eq!(json_path(&LOCATION, &json, "$.data[?@.id > 1].name").unwrap(), vec![json!("jackie")]);
+        assert!(json_path(&LOCATION, &json, "$.missing").unwrap().is_empty());
+        let error = json_path(&LOCATION, &json, "data[0]").unwrap_err();
+        println!("{}", error);
+        assert!(matches!(error, Error::Extract { path, .. } if path == "data[0]"));
+    }
+
+    #[cfg(feature = "xml")]
+    #[test]
+    fn test_xpath() {
+        let xml = "<users><user id=\"1\"><name>linux_china</name></user><user id=\"2\"><name>jackie</name></user></users>";
+        assert_eq!(xpath(&LOCATION, xml, "//user/name").unwrap(), vec!["linux_china", "jackie"]);
+        assert_eq!(xpath(&LOCATION, xml, "//user[2]/@id").unwrap(), vec!["2"]);
+        assert_eq!(xpath(&LOCATION, xml, "count(//user)").unwrap(), vec!["2"]);
+        assert!(xpath(&LOCATION, "{}", "//user").is_err());
+        assert_eq!(extract(&LOCATION, "<token>abc</token>", "/token").unwrap().as_deref(), Some("abc"));
+    }
+
+    #[cfg(not(feature = "xml"))]
+    #[test]
+    fn test_xml_feature_required() {
+        let error = extract(&LOCATION, "<token>abc</token>", "/token").unwrap_err();
+        assert!(matches!(error, Error::Extract { path, message, .. } if path == "/token" && message == "XPath requires `xml` feature of httpfile"));
+    }
+
+    #[test]
+    fn test_extract() {
+        assert_eq!(extract(&LOCATION, r#"{"token": "abc"}"#, "$.token").unwrap().as_deref(), Some("abc"));
+        assert_eq!(extract(&LOCATION, r#"{"token": "abc"}"#, "$.missing").unwrap(), None);
+    }
+}
@@ -0,0 +1,255 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use futures_util::Stream;
+use reqwest::header::HeaderMap;
+use serde_json::{Map, Value};
+use tokio::sync::mpsc;
+#[cfg(feature = "websocket")]
+use futures_util::{SinkExt, StreamExt};
+#[cfg(feature = "websocket")]
+use serde_json::json;
+#[cfg(feature = "websocket")]
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+#[cfg(feature = "websocket")]
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+#[cfg(feature = "websocket")]
+use tokio_tungstenite::tungstenite::Message;
+use crate::{Error, RequestLocation, Result};
+
+/// subprotocol of GraphQL over WebSocket: https://github.com/enisdenjo/graphql-ws/blob/master/PROTOCOL.md
+pub const GRAPHQL_TRANSPORT_WS: &str = "graphql-transport-ws";
+
+/// build GraphQL request body with serde, and variables should be rendered json object
+pub fn to_body(location: &RequestLocation, query: &str, operation_name: Option<&str>, variables: Option<&str>) -> Result<String> {
+    let mut doc = Map::new();
+    doc.insert("query".to_owned(), Value::String(query.to_string()));
+    if let Some(operation_name) = operation_name {
+        doc.insert("operationName".to_owned(), Value::String(operation_name.to_string()));
+    }
+    if let Some(variables) = variables.map(str::trim).filter(|variables| !variables.is_empty()) {
+        let variables: Value = serde_json::from_str(variables).map_err(|e| Error::Template { location: location.clone(), message: format!("illegal GraphQL variables: {}", e) })?;
+        doc.insert("variables".to_owned(), variables);
+    }
+    Ok(Value::Object(doc).to_string())
+}
+
+/// escape text as content of json string, and it's escape function of GraphQL variables templates,
+/// such as `{"name": "{{nick}}"}`, instead of html escaping
+pub fn escape_json(text: &str) -> String {
+    let quoted = Value::String(text.to_string()).to_string();
+    quoted[1..(quoted.len() - 1)].to_string()
+}
+
+/// convert http(s) url of GraphQL endpoint to ws(s) url
+pub fn to_websocket_url(url: &str) -> String {
+    if let Some(address) = url.strip_prefix("https://") {
+        format!("wss://{}", address)
+    } else if let Some(address) = url.strip_prefix("http://") {
+        format!("ws://{}", address)
+    } else {
+        url.to_string()
+    }
+}
+
+/// payloads of `next` messages for GraphQL subscription, such as `{"data": {...}}`
+pub struct SubscriptionStream {
+    receiver: mpsc::Receiver<Result<Value>>,
+}
+
+impl SubscriptionStream {
+    /// next payload, and `None` after subscription completed
+    pub async fn next_payload(&mut self) -> Option<Result<Value>> {
+        self.receiver.recv().await
+    }
+}
+
+impl Stream for SubscriptionStream {
+    type Item = Result<Value>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// subscribe GraphQL subscription with `graphql-transport-ws` protocol, and subscription is completed after stream dropped
+#[cfg(feature = "websocket")]
+pub async fn subscribe(location: &RequestLocation, url: &str, headers: HeaderMap, body: &str) -> Result<SubscriptionStream> {
+    let location = location.clone();
+    let websocket_error = move |message: String| Error::WebSocket { location: location.clone(), message };
+    let payload: Value = serde_json::from_str(body).map_err(|e| websocket_error(e.to_string()))?;
+    let mut request = to_websocket_url(url).into_client_request().map_err(|e| websocket_error(e.to_string()))?;
+    for (name, value) in headers.iter() {
+        let name = HeaderName::from_bytes(name.as_str().as_bytes()).map_err(|e| websocket_error(e.to_string()))?;
+        let value = HeaderValue::from_bytes(value.as_bytes()).map_err(|e| websocket_error(e.to_string()))?;
+        request.headers_mut().insert(name, value);
+    }
+    request.headers_mut().insert("Sec-WebSocket-Protocol", HeaderValue::from_static(GRAPHQL_TRANSPORT_WS));
+    let (mut websocket, _) = tokio_tungstenite::connect_async(request).await.map_err(|e| websocket_error(e.to_string()))?;
+    let init_message = json!({"type": "connection_init", "payload": {}}).to_string();
+    websocket.send(Message::text(init_message)).await.map_err(|e| websocket_error(e.to_string()))?;
+    // wait for connection acknowledged
+    loop {
+        match websocket.next().await {
+            Some(Ok(Message::Text(text))) => match serde_json::from_str::<Value>(&text).unwrap_or_default()["type"].as_str() {
+                Some("connection_ack") => break,
+                Some("ping") => websocket.send(Message::text(json!({"type": "pong"}).to_string())).await.map_err(|e| websocket_error(e.to_string()))?,
+                _ => return Err(websocket_error(format!("unexpected message before connection_ack: {}", text))),
+            },
+            Some(Ok(Message::Close(frame))) => return Err(websocket_error(format!("connection closed: {:?}", frame))),
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(websocket_error(e.to_string())),
+            None => return Err(websocket_error("connection closed before connection_ack".to_owned())),
+        }
+    }
+    let subscribe_message = json!({"id": "1", "type": "subscribe", "payload": payload}).to_string();
+    websocket.send(Message::text(subscribe_message)).await.map_err(|e| websocket_error(e.to_string()))?;
+    let (mut sink, mut stream) = websocket.split();
+    let (sender, receiver) = mpsc::channel(16);
+    tokio::spawn(async move {
+        loop {
+            let incoming = tokio::select! {
+                incoming = stream.next() => incoming,
+                _ = sender.closed() => {
+                    let _ = sink.send(Message::text(json!({"id": "1", "type": "complete"}).to_string())).await;
+                    let _ = sink.close().await;
+                    return;
+                }
+            };
+            let text = match incoming {
+                Some(Ok(Message::Text(text))) => text,
+                Some(Ok(Message::Close(_))) | None => return,
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => {
+                    let _ = sender.send(Err(websocket_error(e.to_string()))).await;
+                    return;
+                }
+            };
+            let mut message = serde_json::from_str::<Value>(&text).unwrap_or_default();
+            match message["type"].as_str() {
+                Some("next") => {
+                    let sent = sender.send(Ok(message["payload"].take())).await;
+                    if sent.is_err() {
+                        let _ = sink.close().await;
+                        return;
+                    }
+                }
+                Some("error") => {
+                    let _ = sender.send(Err(websocket_error(format!("subscription error: {}", message["payload"])))).await;
+                    let _ = sink.close().await;
+                    return;
+                }
+                Some("complete") => {
+                    let _ = sink.close().await;
+                    return;
+                }
+                Some("ping") => {
+                    let _ = sink.send(Message::text(json!({"type": "pong"}).to_string())).await;
+                }
+                _ => {}
+            }
+        }
+    });
+    Ok(SubscriptionStream { receiver })
+}
+
+/// subscribe GraphQL subscription, and `websocket` feature of httpfile is required
+#[cfg(not(feature = "websocket"))]
+pub async fn subscribe(location: &RequestLocation, _url: &str, _headers: HeaderMap, _body: &str) -> Result<SubscriptionStream> {
+    Err(Error::WebSocket { location: location.clone(), message: "GraphQL subscription requires `websocket` feature of httpfile".to_owned() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    #[cfg(feature = "websocket")]
+    use futures_util::StreamExt;
+    #[cfg(feature = "websocket")]
+    use tokio::net::TcpListener;
+    #[cfg(feature = "websocket")]
+    use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+
+    const LOCATION: RequestLocation = RequestLocation::new("on-message", "index.http", 3);
+
+    /// mock server: acknowledges connection, and sends two payloads with operationName then completes
+    #[cfg(feature = "websocket")]
+    async fn start_graphql_ws_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            // subprotocol should be answered, otherwise client rejects the connection
+            #[allow(clippy::result_large_err)]
+            let callback = |request: &Request, mut response: Response| {
+                assert_eq!(request.headers().get("Sec-WebSocket-Protocol").unwrap(), GRAPHQL_TRANSPORT_WS);
+                assert_eq!(request.headers().get("x-token").unwrap(), "secret");
+                response.headers_mut().insert("Sec-WebSocket-Protocol", HeaderValue::from_static(GRAPHQL_TRANSPORT_WS));
+                Ok(response)
+            };
+            let mut websocket = tokio_tungstenite::accept_hdr_async(stream, callback).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = websocket.next().await {
+                let message: Value = serde_json::from_str(&text).unwrap();
+                match message["type"].as_str().unwrap() {
+                    "connection_init" => websocket.send(Message::text(r#"{"type": "connection_ack"}"#)).await.unwrap(),
+                    "subscribe" => {
+                        let operation_name = message["payload"]["operationName"].as_str().unwrap().to_string();
+                        for i in 1..=2 {
+                            let next = json!({"id": "1", "type": "next", "payload": {"data": {"message": format!("{} {}", operation_name, i)}}});
+                            websocket.send(Message::text(next.to_string())).await.unwrap();
+                        }
+                        websocket.send(Message::text(r#"{"id": "1", "type": "complete"}"#)).await.unwrap();
+                    }
+                    _ => {}
+                }
+            }
+        });
+        format!("http://{}/graphql", address)
+    }
+
+    #[test]
+    fn test_to_body() {
+        let body = to_body(&LOCATION, "query { user(name: \"a\\tb\") { id } }", Some("GetUser"), Some(r#"{"id": 1}"#)).unwrap();
+        println!("{}", body);
+        let json: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["query"], "query { user(name: \"a\\tb\") { id } }");
+        assert_eq!(json["operationName"], "GetUser");
+        assert_eq!(json["variables"]["id"], 1);
+        assert!(matches!(to_body(&LOCATION, "{ ip }", None, Some("{ id: 1 }")), Err(Error::Template { .. })));
+        assert_eq!(to_websocket_url("https://example.com/graphql"), "wss://example.com/graphql");
+    }
+
+    #[test]
+    fn test_escape_json() {
+        let mut handlebars = handlebars::Handlebars::new();
+        handlebars.register_escape_fn(escape_json);
+        handlebars.register_template_string("get_user_variables", r#"{"name": "{{nick}}", "id": {{id}}}"#).unwrap();
+        let nick = "a\"b \\ O'Brien\nnext";
+        let params = HashMap::from([("nick", nick), ("id", "1")]);
+        let variables = crate::render_template(&LOCATION, &handlebars, "get_user_variables", &params, &["nick", "id"]).unwrap();
+        println!("{}", variables);
+        let body = to_body(&LOCATION, "query GetUser { user { id } }", None, Some(&variables)).unwrap();
+        let json: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["variables"]["name"], nick);
+        assert_eq!(json["variables"]["id"], 1);
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn test_subscribe() {
+        let url = start_graphql_ws_server().await;
+        let body = to_body(&LOCATION, "subscription OnMessage { message }", Some("OnMessage"), None).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-token", reqwest::header::HeaderValue::from_static("secret"));
+        let mut stream = subscribe(&LOCATION, &url, headers, &body).await.unwrap();
+        assert_eq!(stream.next_payload().await.unwrap().unwrap()["data"]["message"], "OnMessage 1");
+        assert_eq!(stream.next().await.unwrap().unwrap()["data"]["message"], "OnMessage 2");
+        assert!(stream.next_payload().await.is_none());
+    }
+
+    #[cfg(not(feature = "websocket"))]
+    #[tokio::test]
+    async fn test_subscribe_feature_required() {
+        let error = subscribe(&LOCATION, "http://127.0.0.1:1/graphql", HeaderMap::new(), "{}").await.err().unwrap();
+        assert!(error.to_string().contains("GraphQL subscription requires `websocket` feature of httpfile"));
+    }
+}
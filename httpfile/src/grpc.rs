@@ -0,0 +1,55 @@
+use reqwest::header::HeaderMap;
+use serde::de::DeserializeOwned;
+use crate::{Error, RequestLocation, Result};
+
+#[cfg(feature = "grpc")]
+mod client;
+
+#[cfg(feature = "grpc")]
+pub use client::{DynamicCodec, DynamicDecoder, DynamicEncoder};
+
+/// call unary method with json body, such as `http://localhost:50051/helloworld.Greeter/SayHello`,
+/// and descriptors are loaded from encoded `FileDescriptorSet` or server reflection if absent
+#[cfg(feature = "grpc")]
+pub async fn call<T: DeserializeOwned>(location: &RequestLocation, url: &str, headers: HeaderMap, body: &str, descriptor: Option<&[u8]>) -> Result<T> {
+    let json = client::unary(location, url, headers, body, descriptor).await?;
+    decode(location, json)
+}
+
+/// call unary method with json body, and `grpc` feature of httpfile is required
+#[cfg(not(feature = "grpc"))]
+pub async fn call<T: DeserializeOwned>(location: &RequestLocation, _url: &str, _headers: HeaderMap, _body: &str, _descriptor: Option<&[u8]>) -> Result<T> {
+    Err(Error::Grpc { location: location.clone(), code: "Unimplemented".to_owned(), message: "`GRPC` request requires `grpc` feature of httpfile".to_owned() })
+}
+
+/// decode json output of method, such as `//@response-type` of `GRPC` request after assertions checked
+pub fn decode<T: DeserializeOwned>(location: &RequestLocation, json: serde_json::Value) -> Result<T> {
+    serde_json::from_value(json.clone()).map_err(|e| Error::Decode { location: location.clone(), status: 200, body: json.to_string(), message: e.to_string() })
+}
+
+/// origin, service and method from url, such as `http://localhost:50051`, `helloworld.Greeter` and `SayHello`
+pub fn split_url(url: &str) -> Option<(&str, &str, &str)> {
+    let scheme_offset = url.find("://").map(|offset| offset + 3)?;
+    let path_offset = scheme_offset + url[scheme_offset..].find('/')?;
+    let (service_name, method_name) = url[(path_offset + 1)..].trim_end_matches('/').split_once('/')?;
+    Some((&url[..path_offset], service_name, method_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_url() {
+        assert_eq!(split_url("http://localhost:50051/helloworld.Greeter/SayHello"), Some(("http://localhost:50051", "helloworld.Greeter", "SayHello")));
+        assert_eq!(split_url("localhost:50051/helloworld.Greeter/SayHello"), None);
+    }
+
+    #[cfg(not(feature = "grpc"))]
+    #[tokio::test]
+    async fn test_grpc_feature_required() {
+        let url = "http://localhost:50051/helloworld.Greeter/SayHello";
+        let error = call::<serde_json::Value>(&RequestLocation::new("say-hello", "index.http", 3), url, HeaderMap::new(), "{}", None).await.unwrap_err();
+        assert!(error.to_string().contains("`GRPC` request requires `grpc` feature of httpfile"));
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use prost::Message;
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor, MethodDescriptor};
+use reqwest::header::HeaderMap;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::metadata::{MetadataKey, MetadataValue};
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use tonic::Status;
+use tonic_reflection::pb::v1alpha::server_reflection_client::ServerReflectionClient;
+use tonic_reflection::pb::v1alpha::server_reflection_request::MessageRequest;
+use tonic_reflection::pb::v1alpha::server_reflection_response::MessageResponse;
+use tonic_reflection::pb::v1alpha::ServerReflectionRequest;
+use super::split_url;
+use crate::{Error, RequestLocation, Result};
+
+lazy_static::lazy_static! {
+    /// descriptors from server reflection by origin and service
+    static ref REFLECTION_POOLS: Mutex<HashMap<(String, String), DescriptorPool>> = Mutex::new(HashMap::new());
+}
+
+/// protobuf codec for dynamic messages, and decoded messages are output of method for client or input for server
+#[derive(Clone)]
+pub struct DynamicCodec {
+    decode_descriptor: MessageDescriptor,
+}
+
+impl DynamicCodec {
+    pub fn new(decode_descriptor: MessageDescriptor) -> DynamicCodec {
+        DynamicCodec { decode_descriptor }
+    }
+}
+
+pub struct DynamicEncoder;
+
+pub struct DynamicDecoder {
+    decode_descriptor: MessageDescriptor,
+}
+
+impl Codec for DynamicCodec {
+    type Encode = DynamicMessage;
+    type Decode = DynamicMessage;
+    type Encoder = DynamicEncoder;
+    type Decoder = DynamicDecoder;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        DynamicEncoder
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        DynamicDecoder { decode_descriptor: self.decode_descriptor.clone() }
+    }
+}
+
+impl Encoder for DynamicEncoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> std::result::Result<(), Self::Error> {
+        item.encode(dst).map_err(|e| Status::internal(e.to_string()))
+    }
+}
+
+impl Decoder for DynamicDecoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> std::result::Result<Option<Self::Item>, Self::Error> {
+        DynamicMessage::decode(self.decode_descriptor.clone(), src).map(Some).map_err(|e| Status::internal(e.to_string()))
+    }
+}
+
+/// call unary method with json body, and response message is converted to json
+pub async fn unary(location: &RequestLocation, url: &str, headers: HeaderMap, body: &str, descriptor: Option<&[u8]>) -> Result<serde_json::Value> {
+    let grpc_error = |code: String, message: String| Error::Grpc { location: location.clone(), code, message };
+    let invalid_request = |message: String| grpc_error("InvalidArgument".to_string(), message);
+    let (origin, service_name, method_name) = split_url(url).ok_or_else(|| invalid_request(format!("illegal gRPC url: {}", url)))?;
+    let channel = connect(origin).await.map_err(|e| grpc_error("Unavailable".to_string(), e.to_string()))?;
+    let pool = match descriptor {
+        Some(descriptor) => DescriptorPool::decode(descriptor).map_err(|e| invalid_request(e.to_string()))?,
+        None => reflection_pool(channel.clone(), origin, service_name).await.map_err(|status| grpc_error(format!("{:?}", status.code()), status.message().to_string()))?,
+    };
+    let method = find_method(&pool, service_name, method_name).ok_or_else(|| invalid_request(format!("method not found: {}/{}", service_name, method_name)))?;
+    if method.is_client_streaming() || method.is_server_streaming() {
+        return Err(invalid_request(format!("streaming method is not supported: {}", method.full_name())));
+    }
+    let body = if body.trim().is_empty() { "{}" } else { body };
+    let mut deserializer = serde_json::Deserializer::from_str(body);
+    let message = DynamicMessage::deserialize(method.input(), &mut deserializer).map_err(|e| invalid_request(e.to_string()))?;
+    let mut request = tonic::Request::new(message);
+    for (name, value) in headers.iter() {
+        let key = MetadataKey::from_bytes(name.as_str().as_bytes()).map_err(|e| invalid_request(e.to_string()))?;
+        let value = MetadataValue::try_from(value.as_bytes()).map_err(|e| invalid_request(e.to_string()))?;
+        request.metadata_mut().insert(key, value);
+    }
+    let path = PathAndQuery::try_from(format!("/{}/{}", service_name, method_name)).map_err(|e| invalid_request(e.to_string()))?;
+    let mut grpc = tonic::client::Grpc::new(channel);
+    grpc.ready().await.map_err(|e| grpc_error("Unavailable".to_string(), e.to_string()))?;
+    let response = grpc
+        .unary(request, path, DynamicCodec::new(method.output()))
+        .await
+        .map_err(|status| grpc_error(format!("{:?}", status.code()), status.message().to_string()))?;
+    serde_json::to_value(response.into_inner()).map_err(|e| grpc_error("Internal".to_string(), e.to_string()))
+}
+
+async fn connect(origin: &str) -> std::result::Result<Channel, tonic::transport::Error> {
+    let mut endpoint = Endpoint::from_shared(origin.to_string())?;
+    if origin.starts_with("https://") {
+        endpoint = endpoint.tls_config(ClientTlsConfig::new().with_native_roots())?;
+    }
+    endpoint.connect().await
+}
+
+fn find_method(pool: &DescriptorPool, service_name: &str, method_name: &str) -> Option<MethodDescriptor> {
+    pool.get_service_by_name(service_name)?.methods().find(|method| method.name() == method_name)
+}
+
+/// descriptors of the service and its dependencies from `grpc.reflection.v1alpha.ServerReflection`
+async fn reflection_pool(channel: Channel, origin: &str, service_name: &str) -> std::result::Result<DescriptorPool, Status> {
+    let key = (origin.to_string(), service_name.to_string());
+    if let Some(pool) = REFLECTION_POOLS.lock().unwrap().get(&key) {
+        return Ok(pool.clone());
+    }
+    let mut client = ServerReflectionClient::new(channel);
+    let mut files: Vec<prost_types::FileDescriptorProto> = Vec::new();
+    let mut pending = vec![MessageRequest::FileContainingSymbol(service_name.to_string())];
+    while let Some(message_request) = pending.pop() {
+        let request = ServerReflectionRequest { host: String::new(), message_request: Some(message_request) };
+        let mut responses = client.server_reflection_info(tokio_stream::once(request)).await?.into_inner();
+        while let Some(response) = responses.message().await? {
+            match response.message_response {
+                Some(MessageResponse::FileDescriptorResponse(descriptors)) => {
+                    for bytes in descriptors.file_descriptor_proto {
+                        let file = prost_types::FileDescriptorProto::decode(bytes.as_slice()).map_err(|e| Status::internal(e.to_string()))?;
+                        if !files.iter().any(|other| other.name == file.name) {
+                            files.push(file);
+                        }
+                    }
+                }
+                Some(MessageResponse::ErrorResponse(error)) => return Err(Status::new(error.error_code.into(), error.error_message)),
+                _ => {}
+            }
+        }
+        // dependencies may be omitted by server if they were sent before
+        for file in &files {
+            for dependency in &file.dependency {
+                let requested = pending.iter().any(|request| matches!(request, MessageRequest::FileByFilename(name) if name == dependency));
+                if !requested && !files.iter().any(|other| other.name() == dependency) {
+                    pending.push(MessageRequest::FileByFilename(dependency.clone()));
+                }
+            }
+        }
+    }
+    let mut pool = DescriptorPool::new();
+    // files are added after their dependencies
+    while !files.is_empty() {
+        let offset = files
+            .iter()
+            .position(|file| file.dependency.iter().all(|dependency| pool.get_file_by_name(dependency).is_some()))
+            .ok_or_else(|| Status::internal("dependencies of reflected files not found"))?;
+        pool.add_file_descriptor_proto(files.remove(offset)).map_err(|e| Status::internal(e.to_string()))?;
+    }
+    REFLECTION_POOLS.lock().unwrap().insert(key, pool.clone());
+    Ok(pool)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::call;
+    use std::convert::Infallible;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tonic::body::Body;
+    use tonic::codegen::{http, Service};
+    use tonic::server::{NamedService, UnaryService};
+    use tokio::net::TcpListener;
+
+    const LOCATION: RequestLocation = RequestLocation::new("say-hello", "index.http", 3);
+
+    fn greeter_descriptor() -> Vec<u8> {
+        let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        protox::compile(["greeter.proto"], [fixtures]).unwrap().encode_to_vec()
+    }
+
+    /// `helloworld.Greeter` service with dynamic messages
+    #[derive(Clone)]
+    struct Greeter {
+        method: MethodDescriptor,
+    }
+
+    impl NamedService for Greeter {
+        const NAME: &'static str = "helloworld.Greeter";
+    }
+
+    struct SayHello {
+        method: MethodDescriptor,
+    }
+
+    impl UnaryService<DynamicMessage> for SayHello {
+        type Response = DynamicMessage;
+        type Future = Pin<Box<dyn Future<Output = std::result::Result<tonic::Response<DynamicMessage>, Status>> + Send>>;
+
+        fn call(&mut self, request: tonic::Request<DynamicMessage>) -> Self::Future {
+            let token = request.metadata().get("x-token").and_then(|value| value.to_str().ok()).unwrap_or_default().to_string();
+            let request = request.into_inner();
+            let name = request.get_field_by_name("name").unwrap().as_str().unwrap_or_default().to_string();
+            let times = request.get_field_by_name("times").unwrap().as_i32().unwrap_or_default();
+            let mut reply = DynamicMessage::new(self.method.output());
+            reply.set_field_by_name("message", prost_reflect::Value::String(format!("Hello {} x{} {}", name, times, token)));
+            Box::pin(async move { Ok(tonic::Response::new(reply)) })
+        }
+    }
+
+    impl Service<http::Request<Body>> for Greeter {
+        type Response = http::Response<Body>;
+        type Error = Infallible;
+        type Future = Pin<Box<dyn Future<Output = std::result::Result<Self::Response, Self::Error>> + Send>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+            let method = self.method.clone();
+            Box::pin(async move {
+                let mut grpc = tonic::server::Grpc::new(DynamicCodec::new(method.input()));
+                Ok(grpc.unary(SayHello { method }, request).await)
+            })
+        }
+    }
+
+    async fn start_greeter_server() -> String {
+        let descriptor = greeter_descriptor();
+        let pool = DescriptorPool::decode(descriptor.as_slice()).unwrap();
+        let method = find_method(&pool, "helloworld.Greeter", "SayHello").unwrap();
+        let reflection = tonic_reflection::server::Builder::configure().register_encoded_file_descriptor_set(&descriptor).build_v1alpha().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            tonic::transport::Server::builder()
+                .add_service(Greeter { method })
+                .add_service(reflection)
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+                .await
+                .unwrap();
+        });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn test_call() {
+        let origin = start_greeter_server().await;
+        let url = format!("{}/helloworld.Greeter/SayHello", origin);
+        let mut headers = HeaderMap::new();
+        headers.insert("x-token", "secret".parse().unwrap());
+        let descriptor = greeter_descriptor();
+        let reply: serde_json::Value = call(&LOCATION, &url, headers.clone(), r#"{"name": "Rust", "times": 2}"#, Some(&descriptor)).await.unwrap();
+        assert_eq!(reply["message"], "Hello Rust x2 secret");
+        // descriptors from server reflection
+        let reply: serde_json::Value = call(&LOCATION, &url, headers, r#"{"name": "Reflection"}"#, None).await.unwrap();
+        assert_eq!(reply["message"], "Hello Reflection x0 secret");
+        let result = call::<serde_json::Value>(&LOCATION, &format!("{}/helloworld.Greeter/SayBye", origin), HeaderMap::new(), "{}", None).await;
+        assert!(matches!(result, Err(Error::Grpc { .. })));
+    }
+}
@@ -0,0 +1,98 @@
+use reqwest::Response;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::{Error, RequestLocation, Result};
+
+/// error object of JSON-RPC 2.0 response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+/// result of one call in batch response
+pub type RpcResult = std::result::Result<Value, RpcError>;
+
+/// unwrap `result` of JSON-RPC response, and `error` object is returned as `JsonRpc` error
+pub async fn decode_result<T: DeserializeOwned>(location: &RequestLocation, response: Response) -> Result<T> {
+    let status = response.status().as_u16();
+    let bytes = response.bytes().await.map_err(|error| Error::Transport { location: location.clone(), error })?;
+    parse_result(location, status, &bytes)
+}
+
+/// unwrap results of batch response, and results are sorted by id in request order
+pub async fn decode_batch(location: &RequestLocation, response: Response) -> Result<Vec<RpcResult>> {
+    let status = response.status().as_u16();
+    let bytes = response.bytes().await.map_err(|error| Error::Transport { location: location.clone(), error })?;
+    parse_batch(location, status, &bytes)
+}
+
+pub fn parse_result<T: DeserializeOwned>(location: &RequestLocation, status: u16, body: &[u8]) -> Result<T> {
+    let decode_error = |message: String| Error::Decode { location: location.clone(), status, body: String::from_utf8_lossy(body).to_string(), message };
+    let json: Value = serde_json::from_slice(body).map_err(|e| decode_error(e.to_string()))?;
+    let result = to_rpc_result(json).map_err(|error| to_error(location, error))?;
+    serde_json::from_value(result).map_err(|e| decode_error(e.to_string()))
+}
+
+pub fn parse_batch(location: &RequestLocation, status: u16, body: &[u8]) -> Result<Vec<RpcResult>> {
+    let decode_error = |message: String| Error::Decode { location: location.clone(), status, body: String::from_utf8_lossy(body).to_string(), message };
+    let mut items = match serde_json::from_slice(body).map_err(|e| decode_error(e.to_string()))? {
+        Value::Array(items) => items,
+        // server answers single error object if batch itself is illegal
+        json => return Err(to_error(location, to_rpc_result(json).err().unwrap_or_else(|| illegal_response("batch response should be array")))),
+    };
+    items.sort_by_key(|item| item.get("id").and_then(Value::as_u64).unwrap_or(u64::MAX));
+    Ok(items.into_iter().map(to_rpc_result).collect())
+}
+
+fn to_rpc_result(mut json: Value) -> RpcResult {
+    if let Some(error) = json.get_mut("error").map(Value::take) {
+        return Err(serde_json::from_value(error).unwrap_or_else(|e| illegal_response(&e.to_string())));
+    }
+    match json.get_mut("result") {
+        Some(result) => Ok(result.take()),
+        None => Err(illegal_response("neither result nor error found")),
+    }
+}
+
+fn illegal_response(message: &str) -> RpcError {
+    // -32603: internal error of JSON-RPC 2.0
+    RpcError { code: -32603, message: message.to_string(), data: None }
+}
+
+fn to_error(location: &RequestLocation, error: RpcError) -> Error {
+    Error::JsonRpc { location: location.clone(), code: error.code, message: error.message, data: error.data.map(|data| data.to_string()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCATION: RequestLocation = RequestLocation::new("get-balance", "index.http", 3);
+
+    #[test]
+    fn test_parse_result() {
+        let balance: String = parse_result(&LOCATION, 200, br#"{"jsonrpc": "2.0", "id": 1, "result": "0x0234c8a3397aab58"}"#).unwrap();
+        assert_eq!(balance, "0x0234c8a3397aab58");
+        let error = parse_result::<Value>(&LOCATION, 200, br#"{"jsonrpc": "2.0", "id": 1, "error": {"code": -32601, "message": "Method not found"}}"#).unwrap_err();
+        println!("{}", error);
+        assert!(matches!(error, Error::JsonRpc { code: -32601, .. }));
+        assert!(matches!(parse_result::<Value>(&LOCATION, 502, b"Bad Gateway").unwrap_err(), Error::Decode { status: 502, .. }));
+    }
+
+    #[test]
+    fn test_parse_batch() {
+        let body = br#"[
+          {"jsonrpc": "2.0", "id": 2, "error": {"code": -32602, "message": "Invalid params", "data": "address"}},
+          {"jsonrpc": "2.0", "id": 1, "result": "0x4b7"}
+        ]"#;
+        let results = parse_batch(&LOCATION, 200, body).unwrap();
+        assert_eq!(results[0], Ok(Value::String("0x4b7".to_string())));
+        assert_eq!(results[1].as_ref().unwrap_err().data, Some(Value::String("address".to_string())));
+        let error = parse_batch(&LOCATION, 200, br#"{"jsonrpc": "2.0", "id": null, "error": {"code": -32600, "message": "Invalid Request"}}"#).unwrap_err();
+        assert!(matches!(error, Error::JsonRpc { code: -32600, .. }));
+    }
+}
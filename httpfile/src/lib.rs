@@ -1,6 +1,32 @@
 use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
+use handlebars::Handlebars;
 use rand::Rng;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::{ClientBuilder, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub mod assertion;
+pub mod auth;
+pub mod broker;
+pub mod chain;
+pub mod environment;
+mod error;
+pub mod extract;
+pub mod graphql;
+pub mod grpc;
+pub mod jsonrpc;
+pub mod oauth2;
+pub mod proxy;
+pub mod shell;
+pub mod sse;
+pub mod tls;
+pub mod websocket;
+
+pub use error::{Error, RequestLocation, Result};
+
+const DYNAMIC_VARIABLES: [&str; 3] = ["uuid", "timestamp", "randomInt"];
 
 #[macro_export]
 macro_rules! include_http {
@@ -9,14 +35,183 @@ macro_rules! include_http {
     };
 }
 
+/// client builder for generated clients, and registered `SSLConfiguration` and `ProxyConfiguration` are applied
+pub fn client_builder() -> ClientBuilder {
+    proxy::apply_proxy_settings(tls::apply_tls_settings(reqwest::Client::builder()))
+}
+
+/// get variable value from params, and fall back to dynamic variables: `$uuid`, `$timestamp` and `$randomInt`.
+/// unknown variables are kept as they are, such as mistyped `{{$uuidd}}`
+pub fn get_variable_value(params: &HashMap<&str, &str>, name: &str) -> String {
+    let dynamic_name = name.trim_start_matches('$');
+    if let Some(value) = params.get(dynamic_name) {
+        return value.to_string();
+    }
+    match dynamic_name {
+        "uuid" => uuid::Uuid::new_v4().to_string(),
+        "timestamp" => SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs().to_string(),
+        "randomInt" => rand::thread_rng().gen_range(0..1000).to_string(),
+        _ => format!("{{{{{}}}}}", name),
+    }
+}
+
+/// get param for variable, such as `host` or `$uuid`, and `MissingVariable` error if not found
+pub fn get_param(location: &RequestLocation, params: &HashMap<&str, &str>, name: &str) -> Result<String> {
+    if let Some(value) = params.get(name) {
+        return Ok(value.to_string());
+    }
+    let dynamic_name = name.trim_start_matches('$');
+    if params.contains_key(dynamic_name) || DYNAMIC_VARIABLES.contains(&dynamic_name) {
+        return Ok(get_variable_value(params, dynamic_name));
+    }
+    Err(Error::MissingVariable { location: location.clone(), name: name.to_string() })
+}
+
+/// render body template with variables, and all variables should be present in params except dynamic ones
+pub fn render_template(location: &RequestLocation, handlebars: &Handlebars, template_name: &str, params: &HashMap<&str, &str>, variable_names: &[&str]) -> Result<String> {
+    let mut data: HashMap<&str, String> = HashMap::new();
+    for name in variable_names {
+        data.insert(name, get_param(location, params, name)?);
+    }
+    handlebars.render(template_name, &data).map_err(|e| Error::Template { location: location.clone(), message: e.to_string() })
+}
+
+/// serialize body struct to json text
+pub fn to_json<T: Serialize + ?Sized>(location: &RequestLocation, body: &T) -> Result<String> {
+    serde_json::to_string(body).map_err(|e| Error::Template { location: location.clone(), message: e.to_string() })
+}
+
+/// insert header with rendered value, and IDE form `Basic user password` is encoded
+pub fn insert_header(location: &RequestLocation, headers: &mut HeaderMap, name: &str, value: &str) -> Result<()> {
+    let invalid_header = |message: String| Error::InvalidHeader { location: location.clone(), name: name.to_string(), message };
+    let header_name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| invalid_header(e.to_string()))?;
+    let value = if header_name == AUTHORIZATION { auth::encode_basic_auth(value) } else { value.to_string() };
+    let header_value = HeaderValue::from_str(&value).map_err(|e| invalid_header(e.to_string()))?;
+    headers.insert(header_name, header_value);
+    Ok(())
+}
+
+pub async fn send(location: &RequestLocation, request_builder: RequestBuilder) -> Result<Response> {
+    request_builder.send().await.map_err(|error| Error::Transport { location: location.clone(), error })
+}
+
+/// send request with digest credentials from IDE form `Digest user password`
+pub async fn send_with_digest_auth(location: &RequestLocation, request_builder: RequestBuilder, digest_auth: &str) -> Result<Response> {
+    let (user, password) = auth::parse_digest_auth(digest_auth).unwrap_or_default();
+    auth::send_with_digest_auth(request_builder, &user, &password).await.map_err(|error| Error::Transport { location: location.clone(), error })
+}
+
+/// check response status with expected patterns, such as `200` or `2xx`, and `Status` error with body if unexpected
+pub async fn expect_status(location: &RequestLocation, response: Response, expected: &[&str]) -> Result<Response> {
+    let status = response.status().as_u16();
+    let status_text = status.to_string();
+    let matched = expected.iter().any(|pattern| {
+        pattern.len() == 3 && pattern.chars().zip(status_text.chars()).all(|(p, s)| p == 'x' || p == 'X' || p == s)
+    });
+    if matched {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(Error::Status { location: location.clone(), status, body })
+}
+
+/// decode json response body into type, and status with body are kept in error if failed
+pub async fn decode_json<T: DeserializeOwned>(location: &RequestLocation, response: Response) -> Result<T> {
+    let status = response.status().as_u16();
+    let bytes = response.bytes().await.map_err(|error| Error::Transport { location: location.clone(), error })?;
+    serde_json::from_slice(&bytes).map_err(|e| Error::Decode {
+        location: location.clone(),
+        status,
+        body: String::from_utf8_lossy(&bytes).to_string(),
+        message: e.to_string(),
+    })
+}
+
+pub async fn decode_text(location: &RequestLocation, response: Response) -> Result<String> {
+    response.text().await.map_err(|error| Error::Transport { location: location.clone(), error })
+}
+
+pub async fn decode_bytes(location: &RequestLocation, response: Response) -> Result<Vec<u8>> {
+    Ok(response.bytes().await.map_err(|error| Error::Transport { location: location.clone(), error })?.to_vec())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde::Deserialize;
+
+    const LOCATION: RequestLocation = RequestLocation::new("my-ip", "index.http", 3);
+
+    #[derive(Deserialize, Debug)]
+    struct MyIp {
+        origin: String,
+    }
 
     #[test]
     fn test_get_variable_value() {
         let params = HashMap::new();
         let result = get_variable_value(&params, "randomInt");
         println!("{}", result);
+        assert_eq!(get_variable_value(&params, "$uuid").len(), 36);
+        assert_eq!(get_variable_value(&params, "$uuidd"), "{{$uuidd}}");
+    }
+
+    #[test]
+    fn test_get_param() {
+        let params = HashMap::from([("host", "httpbin.org")]);
+        assert_eq!(get_param(&LOCATION, &params, "host").unwrap(), "httpbin.org");
+        assert_eq!(get_param(&LOCATION, &params, "$uuid").unwrap().len(), 36);
+        let error = get_param(&LOCATION, &params, "nick").unwrap_err();
+        assert!(matches!(error, Error::MissingVariable { .. }));
+        assert_eq!(error.to_string(), "my-ip (index.http:3): variable not found: nick");
+    }
+
+    #[test]
+    fn test_render_template() {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_template_string("post_test_body", r#"{"name": "{{nick}}", "id": "{{$uuid}}"}"#).unwrap();
+        let params = HashMap::from([("nick", "Rust")]);
+        let body = render_template(&LOCATION, &handlebars, "post_test_body", &params, &["nick", "$uuid"]).unwrap();
+        assert!(body.starts_with(r#"{"name": "Rust", "id": ""#));
+        let result = render_template(&LOCATION, &handlebars, "post_test_body", &HashMap::new(), &["nick", "$uuid"]);
+        assert!(matches!(result, Err(Error::MissingVariable { .. })));
+    }
+
+    #[test]
+    fn test_insert_header() {
+        let mut headers = HeaderMap::new();
+        insert_header(&LOCATION, &mut headers, "User-Agent", "curl/7.47.0").unwrap();
+        assert!(matches!(insert_header(&LOCATION, &mut headers, "X-Name", "a\nb"), Err(Error::InvalidHeader { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_expect_status() {
+        let response = Response::from(http::Response::builder().status(201).body("").unwrap());
+        assert!(expect_status(&LOCATION, response, &["200", "201"]).await.is_ok());
+        let response = Response::from(http::Response::builder().status(204).body("").unwrap());
+        assert!(expect_status(&LOCATION, response, &["2xx"]).await.is_ok());
+        let response = Response::from(http::Response::builder().status(500).body("oops").unwrap());
+        match expect_status(&LOCATION, response, &["2xx"]).await {
+            Err(Error::Status { status, body, .. }) => {
+                assert_eq!(status, 500);
+                assert_eq!(body, "oops");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decode_json() {
+        let response = Response::from(http::Response::new(r#"{"origin": "127.0.0.1"}"#));
+        let my_ip: MyIp = decode_json(&LOCATION, response).await.unwrap();
+        assert_eq!(my_ip.origin, "127.0.0.1");
+        let response = Response::from(http::Response::builder().status(500).body("oops").unwrap());
+        match decode_json::<MyIp>(&LOCATION, response).await {
+            Err(Error::Decode { status, body, .. }) => {
+                assert_eq!(status, 500);
+                assert_eq!(body, "oops");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
     }
 }
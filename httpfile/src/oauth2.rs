@@ -0,0 +1,474 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use base64::Engine;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use crate::environment::{load_env_files, load_environment};
+use crate::{Error, RequestLocation, Result};
+
+/// token is refreshed if it expires within the margin
+const EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+const AUTHORIZATION_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GrantType {
+    ClientCredentials,
+    Password,
+    AuthorizationCode,
+}
+
+/// OAuth2 config declared in `Security.Auth` of http-client.env.json
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthConfig {
+    pub name: String,
+    pub grant_type: GrantType,
+    pub token_url: String,
+    pub auth_url: Option<String>,
+    pub redirect_url: Option<String>,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    /// send client credentials with basic authorization header(default) or in form body
+    pub client_credentials_in_body: bool,
+    pub scope: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub pkce: bool,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedToken {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_at: Option<Instant>,
+}
+
+impl CachedToken {
+    fn is_fresh(&self) -> bool {
+        self.expires_at.is_none_or(|expires_at| Instant::now() + EXPIRY_MARGIN < expires_at)
+    }
+}
+
+lazy_static::lazy_static! {
+    // token requests take registered `SSLConfiguration` and `ProxyConfiguration` alike generated clients
+    static ref CLIENT: Client = crate::client_builder().build().unwrap();
+    static ref AUTH_CONFIGS: Mutex<HashMap<String, AuthConfig>> = Mutex::new(HashMap::new());
+    static ref TOKENS: Mutex<HashMap<String, CachedToken>> = Mutex::new(HashMap::new());
+    static ref TOKEN_LOCKS: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>> = Mutex::new(HashMap::new());
+    static ref AUTHORIZER: Mutex<fn(&str)> = Mutex::new(print_authorization_url);
+}
+
+fn print_authorization_url(url: &str) {
+    eprintln!("Open the url in browser to authorize: {}", url);
+}
+
+/// load OAuth2 configs of the environment, and `{{name}}` in values are replaced with environment variables
+pub fn load_auth_configs(env_dir: &Path, env_name: &str) -> std::io::Result<HashMap<String, AuthConfig>> {
+    let variables = load_environment(env_dir, env_name)?;
+    // private env file overrides fields of shared one
+    let mut auth_jsons: HashMap<String, serde_json::Map<String, Value>> = HashMap::new();
+    for env_json in load_env_files(env_dir)? {
+        if let Some(Value::Object(auths)) = env_json.pointer(&format!("/{}/Security/Auth", env_name)) {
+            for (name, auth) in auths {
+                if let Value::Object(fields) = auth {
+                    auth_jsons.entry(name.clone()).or_default().extend(fields.clone());
+                }
+            }
+        }
+    }
+    let mut configs = HashMap::new();
+    for (name, fields) in auth_jsons {
+        let text = |key: &str| -> Option<String> {
+            fields.get(key).and_then(|value| value.as_str()).map(|value| {
+                let mut value = value.to_string();
+                for (variable, variable_value) in &variables {
+                    value = value.replace(&format!("{{{{{}}}}}", variable), variable_value);
+                }
+                value
+            })
+        };
+        if text("Type").is_some_and(|auth_type| !auth_type.eq_ignore_ascii_case("OAuth2")) {
+            continue;
+        }
+        let grant_type = match text("Grant Type").unwrap_or_default().to_lowercase().as_str() {
+            "client credentials" => GrantType::ClientCredentials,
+            "password" => GrantType::Password,
+            "authorization code" => GrantType::AuthorizationCode,
+            _ => continue,
+        };
+        let pkce = match fields.get("PKCE") {
+            Some(Value::Bool(pkce)) => *pkce,
+            Some(Value::Object(_)) => true,
+            _ => false,
+        };
+        configs.insert(name.clone(), AuthConfig {
+            name,
+            grant_type,
+            token_url: text("Token URL").unwrap_or_default(),
+            auth_url: text("Auth URL"),
+            redirect_url: text("Redirect URL"),
+            client_id: text("Client ID").unwrap_or_default(),
+            client_secret: text("Client Secret"),
+            client_credentials_in_body: text("Client Credentials").is_some_and(|value| value.eq_ignore_ascii_case("in body")),
+            scope: text("Scope"),
+            username: text("Username"),
+            password: text("Password"),
+            pkce,
+        });
+    }
+    Ok(configs)
+}
+
+/// register OAuth2 configs for `{{$auth.token("name")}}` variables
+pub fn register_auth_configs(configs: HashMap<String, AuthConfig>) {
+    AUTH_CONFIGS.lock().unwrap().extend(configs);
+}
+
+/// set handler to open authorization url for authorization code grant, and url is printed to stderr by default
+pub fn set_authorizer(authorizer: fn(&str)) {
+    *AUTHORIZER.lock().unwrap() = authorizer;
+}
+
+/// auth config name from variable, such as `auth` for `$auth.token("auth")`
+pub fn parse_auth_token_variable(variable: &str) -> Option<&str> {
+    let name = variable.trim().strip_prefix("$auth.token(")?.strip_suffix(')')?.trim();
+    Some(name.trim_matches(|c| c == '"' || c == '\''))
+}
+
+/// access token of the auth config, and cached token is reused or refreshed before expiry.
+/// concurrent calls for the same config wait for one token request
+pub async fn auth_token(name: &str) -> std::result::Result<String, String> {
+    let config = AUTH_CONFIGS.lock().unwrap().get(name).cloned().ok_or_else(|| format!("auth config not found: {}", name))?;
+    let token_lock = TOKEN_LOCKS.lock().unwrap().entry(name.to_string()).or_default().clone();
+    let _guard = token_lock.lock().await;
+    let cached_token = TOKENS.lock().unwrap().get(name).cloned();
+    let token = match cached_token {
+        Some(token) if token.is_fresh() => return Ok(token.access_token),
+        Some(CachedToken { refresh_token: Some(refresh_token), .. }) => match refresh(&config, &refresh_token).await {
+            Ok(token) => token,
+            Err(_) => request_token(&config).await?,
+        },
+        _ => request_token(&config).await?,
+    };
+    let access_token = token.access_token.clone();
+    TOKENS.lock().unwrap().insert(name.to_string(), token);
+    Ok(access_token)
+}
+
+/// values for `$auth.token("name")` variables, and variable names are kept as keys
+pub async fn fetch_tokens(location: &RequestLocation, variables: &[&str]) -> Result<HashMap<String, String>> {
+    let mut tokens = HashMap::new();
+    for variable in variables {
+        let name = parse_auth_token_variable(variable).ok_or_else(|| Error::MissingVariable { location: location.clone(), name: variable.to_string() })?;
+        let token = auth_token(name).await.map_err(|message| Error::Auth { location: location.clone(), name: name.to_string(), message })?;
+        tokens.insert(variable.to_string(), token);
+    }
+    Ok(tokens)
+}
+
+/// params with fetched tokens for generated code
+pub fn merge_params<'a>(params: &HashMap<&'a str, &'a str>, tokens: &'a HashMap<String, String>) -> HashMap<&'a str, &'a str> {
+    let mut merged = params.clone();
+    merged.extend(tokens.iter().map(|(name, value)| (name.as_str(), value.as_str())));
+    merged
+}
+
+async fn request_token(config: &AuthConfig) -> std::result::Result<CachedToken, String> {
+    let mut form: Vec<(&str, String)> = Vec::new();
+    match config.grant_type {
+        GrantType::ClientCredentials => form.push(("grant_type", "client_credentials".to_string())),
+        GrantType::Password => {
+            form.push(("grant_type", "password".to_string()));
+            form.push(("username", config.username.clone().unwrap_or_default()));
+            form.push(("password", config.password.clone().unwrap_or_default()));
+        }
+        GrantType::AuthorizationCode => {
+            let (code, code_verifier) = authorize(config).await?;
+            form.push(("grant_type", "authorization_code".to_string()));
+            form.push(("code", code));
+            if let Some(redirect_url) = &config.redirect_url {
+                form.push(("redirect_uri", redirect_url.clone()));
+            }
+            if let Some(code_verifier) = code_verifier {
+                form.push(("code_verifier", code_verifier));
+            }
+        }
+    }
+    if config.grant_type != GrantType::AuthorizationCode {
+        if let Some(scope) = &config.scope {
+            form.push(("scope", scope.clone()));
+        }
+    }
+    post_token_form(config, form, None).await
+}
+
+async fn refresh(config: &AuthConfig, refresh_token: &str) -> std::result::Result<CachedToken, String> {
+    let form = vec![("grant_type", "refresh_token".to_string()), ("refresh_token", refresh_token.to_string())];
+    post_token_form(config, form, Some(refresh_token)).await
+}
+
+async fn post_token_form(config: &AuthConfig, mut form: Vec<(&str, String)>, refresh_token: Option<&str>) -> std::result::Result<CachedToken, String> {
+    let mut request_builder = CLIENT.post(&config.token_url);
+    if config.client_credentials_in_body || config.client_secret.is_none() {
+        form.push(("client_id", config.client_id.clone()));
+        if let Some(client_secret) = &config.client_secret {
+            form.push(("client_secret", client_secret.clone()));
+        }
+    } else {
+        request_builder = request_builder.basic_auth(&config.client_id, config.client_secret.as_ref());
+    }
+    let response = request_builder.form(&form).send().await.map_err(|e| e.to_string())?;
+    let status = response.status();
+    let body = response.text().await.map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        return Err(format!("token endpoint returned {}: {}", status, body));
+    }
+    let token: TokenResponse = serde_json::from_str(&body).map_err(|e| format!("invalid token response: {}, body: {}", e, body))?;
+    Ok(CachedToken {
+        access_token: token.access_token,
+        // refresh token may be absent in refresh response
+        refresh_token: token.refresh_token.or_else(|| refresh_token.map(|value| value.to_string())),
+        expires_at: token.expires_in.map(|seconds| Instant::now() + Duration::from_secs(seconds)),
+    })
+}
+
+/// authorization code and PKCE code verifier from redirect to local listener
+async fn authorize(config: &AuthConfig) -> std::result::Result<(String, Option<String>), String> {
+    let auth_url = config.auth_url.as_deref().ok_or("Auth URL is required for authorization code grant")?;
+    let redirect_url = config.redirect_url.as_deref().ok_or("Redirect URL is required for authorization code grant")?;
+    let redirect_url = Url::parse(redirect_url).map_err(|e| e.to_string())?;
+    let address = format!("{}:{}", redirect_url.host_str().unwrap_or("localhost"), redirect_url.port_or_known_default().unwrap_or(80));
+    let listener = TcpListener::bind(&address).await.map_err(|e| format!("failed to listen on {}: {}", address, e))?;
+    let state = random_text(16);
+    let code_verifier = config.pkce.then(|| random_text(64));
+    let mut url = Url::parse(auth_url).map_err(|e| e.to_string())?;
+    {
+        let mut query = url.query_pairs_mut();
+        query.append_pair("response_type", "code");
+        query.append_pair("client_id", &config.client_id);
+        query.append_pair("redirect_uri", redirect_url.as_str());
+        query.append_pair("state", &state);
+        if let Some(scope) = &config.scope {
+            query.append_pair("scope", scope);
+        }
+        if let Some(code_verifier) = &code_verifier {
+            query.append_pair("code_challenge", &to_code_challenge(code_verifier));
+            query.append_pair("code_challenge_method", "S256");
+        }
+    }
+    let authorizer = *AUTHORIZER.lock().unwrap();
+    authorizer(url.as_str());
+    let code = tokio::time::timeout(AUTHORIZATION_TIMEOUT, accept_redirect(&listener, redirect_url.path(), &state))
+        .await
+        .map_err(|_| "authorization timed out".to_string())??;
+    Ok((code, code_verifier))
+}
+
+async fn accept_redirect(listener: &TcpListener, path: &str, state: &str) -> std::result::Result<String, String> {
+    loop {
+        let (mut stream, _) = listener.accept().await.map_err(|e| e.to_string())?;
+        let mut buffer = vec![0u8; 8192];
+        let size = stream.read(&mut buffer).await.map_err(|e| e.to_string())?;
+        let request = String::from_utf8_lossy(&buffer[..size]).to_string();
+        let target = request.split_whitespace().nth(1).unwrap_or("/");
+        let url = Url::parse(&format!("http://localhost{}", target)).map_err(|e| e.to_string())?;
+        if url.path() != path {
+            stream.write_all(b"HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n").await.ok();
+            continue;
+        }
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        let result = match (params.get("code"), params.get("error")) {
+            (_, Some(error)) => Err(format!("authorization failed: {}", error)),
+            (Some(_), _) if params.get("state").map(|value| value.as_str()) != Some(state) => Err("authorization state mismatched".to_string()),
+            (Some(code), _) => Ok(code.clone()),
+            _ => Err("authorization code not found in redirect".to_string()),
+        };
+        let message = if result.is_ok() { "Authorization completed, and you can close this window." } else { "Authorization failed." };
+        let response = format!("HTTP/1.1 200 OK\r\ncontent-type: text/plain\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", message.len(), message);
+        stream.write_all(response.as_bytes()).await.ok();
+        return result;
+    }
+}
+
+fn random_text(len: usize) -> String {
+    rand::thread_rng().sample_iter(&Alphanumeric).take(len).map(char::from).collect()
+}
+
+fn to_code_challenge(code_verifier: &str) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+
+    /// mock token endpoint: answers token requests with counter in token, and request bodies are recorded
+    async fn start_token_server(expires_in: u64) -> (String, std::sync::Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let requests = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0u8; 8192];
+                let size = stream.read(&mut buffer).await.unwrap();
+                let request = String::from_utf8_lossy(&buffer[..size]).to_string();
+                let count = {
+                    let mut recorded = recorded.lock().unwrap();
+                    recorded.push(request);
+                    recorded.len()
+                };
+                let body = format!(r#"{{"access_token": "token-{}", "refresh_token": "refresh-{}", "expires_in": {}}}"#, count, count, expires_in);
+                let response = format!("HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", body.len(), body);
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (format!("http://{}/token", address), requests)
+    }
+
+    fn client_config(name: &str, token_url: &str, grant_type: GrantType) -> AuthConfig {
+        AuthConfig {
+            name: name.to_string(),
+            grant_type,
+            token_url: token_url.to_string(),
+            auth_url: None,
+            redirect_url: None,
+            client_id: "demo".to_string(),
+            client_secret: Some("secret".to_string()),
+            client_credentials_in_body: false,
+            scope: Some("read".to_string()),
+            username: Some("admin".to_string()),
+            password: Some("123456".to_string()),
+            pkce: false,
+        }
+    }
+
+    #[test]
+    fn test_load_auth_configs() {
+        let env_dir = std::env::temp_dir().join("httpfile-oauth2-test");
+        std::fs::create_dir_all(&env_dir).unwrap();
+        std::fs::write(env_dir.join("http-client.env.json"), r#"{
+  "dev": {
+    "host": "localhost:8080",
+    "Security": {
+      "Auth": {
+        "demo": {
+          "Type": "OAuth2",
+          "Grant Type": "Authorization Code",
+          "Auth URL": "http://{{host}}/authorize",
+          "Token URL": "http://{{host}}/token",
+          "Redirect URL": "http://127.0.0.1:8765/callback",
+          "Client ID": "demo",
+          "PKCE": true
+        }
+      }
+    }
+  }
+}"#).unwrap();
+        std::fs::write(env_dir.join("http-client.private.env.json"), r#"{"dev": {"Security": {"Auth": {"demo": {"Client Secret": "secret"}}}}}"#).unwrap();
+        let configs = load_auth_configs(&env_dir, "dev").unwrap();
+        let config = configs.get("demo").unwrap();
+        assert_eq!(config.grant_type, GrantType::AuthorizationCode);
+        assert_eq!(config.token_url, "http://localhost:8080/token");
+        assert_eq!(config.client_secret.as_deref(), Some("secret"));
+        assert!(config.pkce);
+    }
+
+    #[test]
+    fn test_parse_auth_token_variable() {
+        assert_eq!(parse_auth_token_variable(r#"$auth.token("demo")"#), Some("demo"));
+        assert_eq!(parse_auth_token_variable("$auth.token('demo')"), Some("demo"));
+        assert_eq!(parse_auth_token_variable("host"), None);
+    }
+
+    #[tokio::test]
+    async fn test_client_credentials_token() {
+        let (token_url, requests) = start_token_server(3600).await;
+        register_auth_configs(HashMap::from([("client".to_string(), client_config("client", &token_url, GrantType::ClientCredentials))]));
+        assert_eq!(auth_token("client").await.unwrap(), "token-1");
+        // cached token is reused
+        assert_eq!(auth_token("client").await.unwrap(), "token-1");
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("grant_type=client_credentials&scope=read"));
+        assert!(requests[0].to_lowercase().contains("authorization: basic zgvtbzpzzwnyzxq="));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_auth_token() {
+        let (token_url, requests) = start_token_server(3600).await;
+        register_auth_configs(HashMap::from([("concurrent".to_string(), client_config("concurrent", &token_url, GrantType::ClientCredentials))]));
+        let (first, second, third) = tokio::join!(auth_token("concurrent"), auth_token("concurrent"), auth_token("concurrent"));
+        assert_eq!(first.unwrap(), "token-1");
+        assert_eq!(second.unwrap(), "token-1");
+        assert_eq!(third.unwrap(), "token-1");
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_token_before_expiry() {
+        let (token_url, requests) = start_token_server(10).await;
+        register_auth_configs(HashMap::from([("password".to_string(), client_config("password", &token_url, GrantType::Password))]));
+        let location = &RequestLocation::new("my-ip", "index.http", 3);
+        let tokens = fetch_tokens(location, &[r#"$auth.token("password")"#]).await.unwrap();
+        assert_eq!(tokens.get(r#"$auth.token("password")"#).unwrap(), "token-1");
+        // token expires within margin, so it is refreshed
+        assert_eq!(auth_token("password").await.unwrap(), "token-2");
+        {
+            let requests = requests.lock().unwrap();
+            assert!(requests[0].contains("grant_type=password&username=admin&password=123456"));
+            assert!(requests[1].contains("grant_type=refresh_token&refresh_token=refresh-1"));
+        }
+        let result = fetch_tokens(location, &[r#"$auth.token("unknown")"#]).await;
+        assert!(matches!(result, Err(Error::Auth { .. })));
+    }
+
+    fn browse(url: &str) {
+        let url = Url::parse(url).unwrap();
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        assert_eq!(params.get("code_challenge_method").unwrap(), "S256");
+        let redirect_url = Url::parse(params.get("redirect_uri").unwrap()).unwrap();
+        let target = format!("{}?code=abc&state={}", redirect_url.path(), params.get("state").unwrap());
+        let address = format!("{}:{}", redirect_url.host_str().unwrap(), redirect_url.port().unwrap());
+        std::thread::spawn(move || {
+            let mut stream = std::net::TcpStream::connect(address).unwrap();
+            stream.write_all(format!("GET {} HTTP/1.1\r\nhost: localhost\r\n\r\n", target).as_bytes()).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+        });
+    }
+
+    #[tokio::test]
+    async fn test_authorization_code_with_pkce() {
+        let (token_url, requests) = start_token_server(3600).await;
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let mut config = client_config("code", &token_url, GrantType::AuthorizationCode);
+        config.auth_url = Some("http://127.0.0.1/authorize".to_string());
+        config.redirect_url = Some(format!("http://127.0.0.1:{}/callback", port));
+        config.client_credentials_in_body = true;
+        config.pkce = true;
+        register_auth_configs(HashMap::from([("code".to_string(), config)]));
+        set_authorizer(browse);
+        assert_eq!(auth_token("code").await.unwrap(), "token-1");
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].contains("grant_type=authorization_code&code=abc"));
+        assert!(requests[0].contains("code_verifier="));
+        assert!(requests[0].contains("client_id=demo&client_secret=secret"));
+    }
+}
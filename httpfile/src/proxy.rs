@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::io::ErrorKind;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use reqwest::{Certificate, Client, ClientBuilder, NoProxy, Proxy};
+use serde_json::Value;
+use crate::environment::load_env_files;
+use crate::{Error, RequestLocation, Result};
+
+/// `ProxyConfiguration` of environment in http-client.env.json, and proxy url may be `http`, `https` or `socks5` scheme
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProxyConfiguration {
+    /// proxy for all requests
+    pub url: Option<String>,
+    /// proxy for http requests
+    pub http: Option<String>,
+    /// proxy for https requests
+    pub https: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// comma separated hosts, domains or IP ranges without proxy, such as `localhost,.internal,10.0.0.0/8`
+    pub no_proxy: Option<String>,
+}
+
+/// set once proxies are applied to a client builder, and later registration would be ignored by built clients
+static PROXIES_APPLIED: AtomicBool = AtomicBool::new(false);
+
+lazy_static::lazy_static! {
+    static ref PROXIES: Mutex<Option<Vec<Proxy>>> = Mutex::new(None);
+    static ref PROXY_CLIENTS: Mutex<HashMap<(String, bool, usize), Client>> = Mutex::new(HashMap::new());
+}
+
+/// load `ProxyConfiguration` of the environment, and private env file overrides fields of shared one
+pub fn load_proxy_configuration(env_dir: &Path, env_name: &str) -> std::io::Result<Option<ProxyConfiguration>> {
+    let mut fields = serde_json::Map::new();
+    for env_json in load_env_files(env_dir)? {
+        if let Some(Value::Object(proxy_fields)) = env_json.pointer(&format!("/{}/ProxyConfiguration", env_name)) {
+            fields.extend(proxy_fields.clone());
+        }
+    }
+    if fields.is_empty() {
+        return Ok(None);
+    }
+    let text = |key: &str| fields.get(key).and_then(|value| value.as_str()).map(|value| value.to_string());
+    Ok(Some(ProxyConfiguration {
+        url: text("url"),
+        http: text("http"),
+        https: text("https"),
+        username: text("username"),
+        password: text("password"),
+        no_proxy: text("noProxy"),
+    }))
+}
+
+/// proxies for the configuration, and credentials with no-proxy list are applied to each proxy
+pub fn to_proxies(config: &ProxyConfiguration) -> reqwest::Result<Vec<Proxy>> {
+    let mut proxies = Vec::new();
+    if let Some(url) = &config.http {
+        proxies.push(Proxy::http(url)?);
+    }
+    if let Some(url) = &config.https {
+        proxies.push(Proxy::https(url)?);
+    }
+    if let Some(url) = &config.url {
+        proxies.push(Proxy::all(url)?);
+    }
+    Ok(proxies
+        .into_iter()
+        .map(|mut proxy| {
+            if let Some(username) = &config.username {
+                proxy = proxy.basic_auth(username, config.password.as_deref().unwrap_or_default());
+            }
+            proxy.no_proxy(config.no_proxy.as_deref().and_then(NoProxy::from_string))
+        })
+        .collect())
+}
+
+/// register `ProxyConfiguration` for clients from `httpfile::client_builder()`, and error is returned if called after
+/// first client is built, such as `CLIENT` of generated code, because built clients keep their proxies
+pub fn register_proxy_configuration(config: &ProxyConfiguration) -> std::io::Result<()> {
+    register_proxies(&PROXIES_APPLIED, &PROXIES, config)
+}
+
+/// apply registered proxies to client builder, and system proxy env vars are used if not registered
+pub fn apply_proxy_settings(client_builder: ClientBuilder) -> ClientBuilder {
+    apply_proxies(&PROXIES_APPLIED, &PROXIES, client_builder)
+}
+
+fn register_proxies(applied: &AtomicBool, registered: &Mutex<Option<Vec<Proxy>>>, config: &ProxyConfiguration) -> std::io::Result<()> {
+    if applied.load(Ordering::SeqCst) {
+        return Err(std::io::Error::other("ProxyConfiguration should be registered before first request"));
+    }
+    let proxies = to_proxies(config).map_err(|e| std::io::Error::new(ErrorKind::InvalidInput, e))?;
+    *registered.lock().unwrap() = Some(proxies);
+    Ok(())
+}
+
+fn apply_proxies(applied: &AtomicBool, registered: &Mutex<Option<Vec<Proxy>>>, mut client_builder: ClientBuilder) -> ClientBuilder {
+    applied.store(true, Ordering::SeqCst);
+    if let Some(proxies) = registered.lock().unwrap().clone() {
+        for proxy in proxies {
+            client_builder = client_builder.proxy(proxy);
+        }
+    }
+    client_builder
+}
+
+/// client builder from `httpfile::client_builder()` with proxy from `@proxy` tag instead of registered proxies,
+/// and `none` or `direct` disables proxy. Root certificates are from Builder for generated code
+pub fn proxy_client_builder(proxy_url: &str, root_certificates: &[Certificate]) -> reqwest::Result<ClientBuilder> {
+    let mut client_builder = crate::client_builder().no_proxy();
+    for certificate in root_certificates {
+        client_builder = client_builder.add_root_certificate(certificate.clone());
+    }
+    match proxy_url.trim() {
+        "none" | "direct" => Ok(client_builder.no_proxy()),
+        proxy_url => Ok(client_builder.proxy(Proxy::all(proxy_url)?)),
+    }
+}
+
+/// cached client for `@proxy` tag in generated code, and `root_certificates` is `ROOT_CERTIFICATES` of generated code
+pub fn client(location: &RequestLocation, proxy_url: &str, insecure: bool, root_certificates: &'static [Certificate]) -> Result<Client> {
+    // generated files have their own root certificates
+    let key = (proxy_url.to_string(), insecure, root_certificates.as_ptr() as usize);
+    if let Some(client) = PROXY_CLIENTS.lock().unwrap().get(&key) {
+        return Ok(client.clone());
+    }
+    let invalid_proxy = |error: reqwest::Error| Error::Proxy { location: location.clone(), proxy: proxy_url.to_string(), message: error.to_string() };
+    let client = proxy_client_builder(proxy_url, root_certificates)
+        .and_then(|client_builder| client_builder.danger_accept_invalid_certs(insecure).build())
+        .map_err(invalid_proxy)?;
+    PROXY_CLIENTS.lock().unwrap().insert(key, client.clone());
+    Ok(client)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// mock http proxy: request line with absolute url is echoed in body
+    async fn start_proxy_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0u8; 4096];
+                let size = stream.read(&mut buffer).await.unwrap();
+                let request = String::from_utf8_lossy(&buffer[..size]).to_string();
+                let body = request.lines().next().unwrap_or_default().to_string();
+                let response = format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", body.len(), body);
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    #[test]
+    fn test_load_proxy_configuration() {
+        let env_dir = std::env::temp_dir().join("httpfile-proxy-test");
+        std::fs::create_dir_all(&env_dir).unwrap();
+        std::fs::write(env_dir.join("http-client.env.json"), r#"{
+  "dev": {
+    "ProxyConfiguration": {
+      "https": "socks5://127.0.0.1:1080",
+      "username": "admin",
+      "noProxy": "localhost,.internal"
+    }
+  }
+}"#).unwrap();
+        std::fs::write(env_dir.join("http-client.private.env.json"), r#"{"dev": {"ProxyConfiguration": {"password": "secret"}}}"#).unwrap();
+        let config = load_proxy_configuration(&env_dir, "dev").unwrap().unwrap();
+        assert_eq!(config.https.as_deref(), Some("socks5://127.0.0.1:1080"));
+        assert_eq!(config.password.as_deref(), Some("secret"));
+        assert_eq!(to_proxies(&config).unwrap().len(), 1);
+        // registration after first client is built is rejected, and test state keeps global proxies untouched
+        let applied = AtomicBool::new(false);
+        let registered = Mutex::new(None);
+        register_proxies(&applied, &registered, &config).unwrap();
+        assert!(apply_proxies(&applied, &registered, reqwest::Client::builder()).build().is_ok());
+        assert!(register_proxies(&applied, &registered, &config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_proxy_client() {
+        let proxy_url = start_proxy_server().await;
+        let location = &RequestLocation::new("my-ip", "index.http", 3);
+        let client = client(location, &proxy_url, false, &[]).unwrap();
+        let response = client.get("http://httpbin.example/ip").send().await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "GET http://httpbin.example/ip HTTP/1.1");
+        let config = ProxyConfiguration { url: Some(proxy_url), no_proxy: Some("127.0.0.1".to_string()), ..Default::default() };
+        let mut client_builder = reqwest::Client::builder();
+        for proxy in to_proxies(&config).unwrap() {
+            client_builder = client_builder.proxy(proxy);
+        }
+        let response = client_builder.build().unwrap().get("http://httpbin.example/ip").send().await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "GET http://httpbin.example/ip HTTP/1.1");
+        assert!(matches!(super::client(location, "not a proxy url", false, &[]), Err(Error::Proxy { .. })));
+        let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        let certificate = crate::tls::root_certificate(&std::fs::read(fixtures.join("client.pem")).unwrap()).unwrap();
+        assert!(proxy_client_builder("none", &[certificate]).unwrap().build().is_ok());
+    }
+}
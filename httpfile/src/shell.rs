@@ -0,0 +1,100 @@
+use reqwest::header::HeaderMap;
+use serde::de::DeserializeOwned;
+use tokio::process::Command;
+use crate::{Error, RequestLocation, Result};
+
+#[cfg(feature = "ssh")]
+mod ssh;
+
+/// output of `SHELL` or `SSH` request, and stdout is used alike response body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandOutput {
+    pub status: i32,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CommandOutput {
+    /// stdout without trailing new line, such as id printed by `echo`
+    pub fn text(&self) -> &str {
+        self.stdout.trim_end()
+    }
+
+    /// decode stdout as json, such as output of `jq` or `kubectl -o json`
+    pub fn json<T: DeserializeOwned>(&self, location: &RequestLocation) -> Result<T> {
+        serde_json::from_str(&self.stdout).map_err(|e| Error::Shell {
+            location: location.clone(),
+            status: Some(self.status),
+            message: format!("failed to decode stdout as json: {}", e),
+        })
+    }
+}
+
+/// run script with interpreter, such as `sh` or `bash`, and headers are exported as environment variables: `X-Token` to `X_TOKEN`
+pub async fn run(location: &RequestLocation, interpreter: &str, headers: &HeaderMap, script: &str) -> Result<CommandOutput> {
+    let interpreter = if interpreter.is_empty() { "sh" } else { interpreter };
+    let mut command = Command::new(interpreter);
+    command.arg("-c").arg(script);
+    for (name, value) in headers {
+        command.env(name.as_str().replace('-', "_").to_uppercase(), value.to_str().unwrap_or_default());
+    }
+    let output = command.output().await.map_err(|e| Error::Shell {
+        location: location.clone(),
+        status: None,
+        message: format!("failed to run {}: {}", interpreter, e),
+    })?;
+    let output = CommandOutput {
+        status: output.status.code().unwrap_or(-1),
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+    };
+    check_status(location, output)
+}
+
+/// run script on remote host, such as `user@host:2222`, and credentials are from `Password`, `Private-Key` and `Passphrase` headers.
+/// host key should be in `~/.ssh/known_hosts`, and unknown host keys are accepted only if insecure
+#[cfg(feature = "ssh")]
+pub async fn ssh(location: &RequestLocation, destination: &str, insecure: bool, headers: &HeaderMap, script: &str) -> Result<CommandOutput> {
+    let output = ssh::exec(location, destination, insecure, headers, script).await?;
+    check_status(location, output)
+}
+
+/// run script on remote host, and `ssh` feature of httpfile is required
+#[cfg(not(feature = "ssh"))]
+pub async fn ssh(location: &RequestLocation, _destination: &str, _insecure: bool, _headers: &HeaderMap, _script: &str) -> Result<CommandOutput> {
+    Err(Error::Shell { location: location.clone(), status: None, message: "`SSH` request requires `ssh` feature of httpfile".to_owned() })
+}
+
+/// non-zero exit status is returned as `Shell` error with stderr
+fn check_status(location: &RequestLocation, output: CommandOutput) -> Result<CommandOutput> {
+    if output.status == 0 {
+        Ok(output)
+    } else {
+        Err(Error::Shell { location: location.clone(), status: Some(output.status), message: output.stderr.trim_end().to_string() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCATION: RequestLocation = RequestLocation::new("seed", "index.http", 3);
+
+    #[tokio::test]
+    async fn test_run() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-user-name", "linux_china".parse().unwrap());
+        let output = run(&LOCATION, "sh", &headers, "echo \"{\\\"name\\\": \\\"$X_USER_NAME\\\"}\"").await.unwrap();
+        println!("{:?}", output);
+        assert_eq!(output.text(), r#"{"name": "linux_china"}"#);
+        let json: serde_json::Value = output.json(&LOCATION).unwrap();
+        assert_eq!(json["name"], "linux_china");
+    }
+
+    #[tokio::test]
+    async fn test_run_failed() {
+        let error = run(&LOCATION, "sh", &HeaderMap::new(), "echo oops >&2\nexit 3").await.unwrap_err();
+        println!("{}", error);
+        assert!(matches!(error, Error::Shell { status: Some(3), message, .. } if message == "oops"));
+    }
+}
@@ -0,0 +1,204 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use reqwest::header::HeaderMap;
+use russh::client::{self, Handle};
+use russh::keys::{self, PrivateKeyWithHashAlg, PublicKey};
+use russh::{ChannelMsg, Disconnect};
+use crate::shell::CommandOutput;
+use crate::{Error, RequestLocation, Result};
+
+/// default private keys in `~/.ssh` if neither `Password` nor `Private-Key` header is present
+const DEFAULT_PRIVATE_KEYS: [&str; 3] = ["id_ed25519", "id_ecdsa", "id_rsa"];
+
+struct Client {
+    host: String,
+    port: u16,
+    /// accept host keys not in `~/.ssh/known_hosts`, such as `@insecure` requests
+    insecure: bool,
+}
+
+impl client::Handler for Client {
+    type Error = russh::Error;
+
+    /// host keys in `~/.ssh/known_hosts` are accepted, unknown hosts only if insecure, and changed host keys are rejected
+    async fn check_server_key(&mut self, server_public_key: &PublicKey) -> std::result::Result<bool, Self::Error> {
+        match keys::check_known_hosts(&self.host, self.port, server_public_key) {
+            Ok(true) => Ok(true),
+            Err(keys::Error::KeyChanged { .. }) => Ok(false),
+            _ => Ok(self.insecure),
+        }
+    }
+}
+
+/// destination alike `user@host:port`, and user is from `USER` environment variable if absent
+fn parse_destination(destination: &str) -> (String, String, u16) {
+    let destination = destination.trim_start_matches("ssh://").trim_end_matches('/');
+    let (user, address) = match destination.rsplit_once('@') {
+        Some((user, address)) => (user.to_string(), address),
+        None => (std::env::var("USER").unwrap_or_else(|_| "root".to_owned()), destination),
+    };
+    match address.rsplit_once(':') {
+        Some((host, port)) if port.parse::<u16>().is_ok() => (user, host.to_string(), port.parse().unwrap()),
+        _ => (user, address.to_string(), 22),
+    }
+}
+
+/// path with `~/` expanded to home directory
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(relative_path), Ok(home)) => PathBuf::from(home).join(relative_path),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn to_error(location: &RequestLocation, message: impl std::fmt::Display) -> Error {
+    Error::Shell { location: location.clone(), status: None, message: message.to_string() }
+}
+
+async fn authenticate(location: &RequestLocation, session: &mut Handle<Client>, user: &str, headers: &HeaderMap) -> Result<()> {
+    let header_value = |name: &str| headers.get(name).and_then(|value| value.to_str().ok()).map(|value| value.to_string());
+    let authenticated = if let Some(password) = header_value("password") {
+        session.authenticate_password(user, password).await.map_err(|e| to_error(location, e))?.success()
+    } else {
+        let private_key_file = match header_value("private-key") {
+            Some(private_key) => expand_home(&private_key),
+            None => DEFAULT_PRIVATE_KEYS
+                .iter()
+                .map(|name| expand_home(&format!("~/.ssh/{}", name)))
+                .find(|path| path.exists())
+                .ok_or_else(|| to_error(location, "private key not found in ~/.ssh, and `Password` or `Private-Key` header is required"))?,
+        };
+        let private_key = keys::load_secret_key(&private_key_file, header_value("passphrase").as_deref())
+            .map_err(|e| to_error(location, format!("failed to load private key {}: {}", private_key_file.display(), e)))?;
+        let hash_alg = session.best_supported_rsa_hash().await.map_err(|e| to_error(location, e))?.flatten();
+        let private_key = PrivateKeyWithHashAlg::new(Arc::new(private_key), hash_alg);
+        session.authenticate_publickey(user, private_key).await.map_err(|e| to_error(location, e))?.success()
+    };
+    if authenticated {
+        Ok(())
+    } else {
+        Err(to_error(location, format!("authentication failed for {}", user)))
+    }
+}
+
+/// execute script in session channel, and stdout with stderr are collected until channel closed
+pub(super) async fn exec(location: &RequestLocation, destination: &str, insecure: bool, headers: &HeaderMap, script: &str) -> Result<CommandOutput> {
+    let (user, host, port) = parse_destination(destination);
+    let config = Arc::new(client::Config::default());
+    let handler = Client { host: host.clone(), port, insecure };
+    let mut session = client::connect(config, (host.as_str(), port), handler).await.map_err(|e| match e {
+        russh::Error::UnknownKey => to_error(location, format!("host key of {}:{} is unknown or changed in ~/.ssh/known_hosts, and `@insecure` tag accepts unknown host keys", host, port)),
+        e => to_error(location, e),
+    })?;
+    authenticate(location, &mut session, &user, headers).await?;
+    let mut channel = session.channel_open_session().await.map_err(|e| to_error(location, e))?;
+    channel.exec(true, script).await.map_err(|e| to_error(location, e))?;
+    let mut stdout: Vec<u8> = Vec::new();
+    let mut stderr: Vec<u8> = Vec::new();
+    let mut status = None;
+    while let Some(message) = channel.wait().await {
+        match message {
+            ChannelMsg::Data { data } => stdout.extend_from_slice(&data),
+            ChannelMsg::ExtendedData { data, ext: 1 } => stderr.extend_from_slice(&data),
+            ChannelMsg::ExitStatus { exit_status } => status = Some(exit_status as i32),
+            _ => {}
+        }
+    }
+    let _ = session.disconnect(Disconnect::ByApplication, "", "en").await;
+    Ok(CommandOutput {
+        status: status.unwrap_or(-1),
+        stdout: String::from_utf8_lossy(&stdout).to_string(),
+        stderr: String::from_utf8_lossy(&stderr).to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use russh::keys::ssh_key::rand_core::OsRng;
+    use russh::keys::{Algorithm, PrivateKey};
+    use russh::server::{self, Auth, Msg, Server, Session};
+    use russh::{Channel, ChannelId, CryptoVec};
+    use tokio::net::TcpListener;
+
+    const LOCATION: RequestLocation = RequestLocation::new("seed", "index.http", 3);
+
+    /// mock server with password `secret`: echoes command as stdout, and exits with status 0
+    #[derive(Clone)]
+    struct EchoServer;
+
+    impl Server for EchoServer {
+        type Handler = Self;
+
+        fn new_client(&mut self, _: Option<std::net::SocketAddr>) -> Self {
+            self.clone()
+        }
+    }
+
+    impl server::Handler for EchoServer {
+        type Error = russh::Error;
+
+        async fn auth_password(&mut self, _user: &str, password: &str) -> std::result::Result<Auth, Self::Error> {
+            if password == "secret" {
+                Ok(Auth::Accept)
+            } else {
+                Ok(Auth::reject())
+            }
+        }
+
+        async fn channel_open_session(&mut self, _channel: Channel<Msg>, _session: &mut Session) -> std::result::Result<bool, Self::Error> {
+            Ok(true)
+        }
+
+        async fn exec_request(&mut self, channel: ChannelId, data: &[u8], session: &mut Session) -> std::result::Result<(), Self::Error> {
+            session.channel_success(channel)?;
+            session.data(channel, CryptoVec::from(data.to_vec()))?;
+            session.exit_status_request(channel, 0)?;
+            session.eof(channel)?;
+            session.close(channel)?;
+            Ok(())
+        }
+    }
+
+    async fn start_ssh_server() -> u16 {
+        let config = server::Config {
+            keys: vec![PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap()],
+            ..Default::default()
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let _ = EchoServer.run_on_socket(Arc::new(config), &listener).await;
+        });
+        port
+    }
+
+    #[test]
+    fn test_parse_destination() {
+        assert_eq!(parse_destination("linux_china@example.com:2222"), ("linux_china".to_owned(), "example.com".to_owned(), 2222));
+        assert_eq!(parse_destination("ssh://root@example.com"), ("root".to_owned(), "example.com".to_owned(), 22));
+    }
+
+    #[tokio::test]
+    async fn test_exec() {
+        let port = start_ssh_server().await;
+        let mut headers = HeaderMap::new();
+        headers.insert("password", "secret".parse().unwrap());
+        let output = exec(&LOCATION, &format!("tester@127.0.0.1:{}", port), true, &headers, "echo hello").await.unwrap();
+        println!("{:?}", output);
+        assert_eq!(output, CommandOutput { status: 0, stdout: "echo hello".to_owned(), stderr: String::new() });
+        headers.insert("password", "wrong".parse().unwrap());
+        let error = exec(&LOCATION, &format!("tester@127.0.0.1:{}", port), true, &headers, "echo hello").await.unwrap_err();
+        assert!(error.to_string().contains("authentication failed for tester"));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_host_rejected() {
+        let port = start_ssh_server().await;
+        let mut headers = HeaderMap::new();
+        headers.insert("password", "secret".parse().unwrap());
+        let error = exec(&LOCATION, &format!("tester@127.0.0.1:{}", port), false, &headers, "echo hello").await.unwrap_err();
+        println!("{}", error);
+        assert!(error.to_string().contains("is unknown or changed in ~/.ssh/known_hosts"));
+    }
+}
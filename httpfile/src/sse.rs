@@ -0,0 +1,239 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use futures_util::{Stream, StreamExt};
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::{RequestBuilder, StatusCode};
+use tokio::sync::mpsc;
+use crate::{Error, RequestLocation, Result};
+
+/// reconnection delay if server doesn't send `retry` field
+const DEFAULT_RETRY: Duration = Duration::from_secs(3);
+
+/// event of `text/event-stream` response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    /// last event id, and it's kept for following events without `id` field
+    pub id: Option<String>,
+    /// event type, and `message` by default
+    pub event: String,
+    pub data: String,
+    /// reconnection time in milliseconds declared with the event
+    pub retry: Option<u64>,
+}
+
+/// incremental parser of `text/event-stream` body
+#[derive(Debug, Default)]
+pub struct EventParser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Option<String>,
+    retry: Option<u64>,
+    last_event_id: Option<String>,
+}
+
+impl EventParser {
+    /// parse received bytes, and complete events are returned
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<Event> {
+        self.buffer.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        while let Some(offset) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=offset).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches('\n').trim_end_matches('\r');
+            if let Some(event) = self.parse_line(line) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
+    }
+
+    fn parse_line(&mut self, line: &str) -> Option<Event> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => match &mut self.data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_string()),
+            },
+            "id" if !value.contains('\0') => self.last_event_id = Some(value.to_string()),
+            "retry" => self.retry = value.parse().ok().or(self.retry),
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<Event> {
+        let event = self.event.take();
+        let retry = self.retry.take();
+        let data = self.data.take()?;
+        Some(Event { id: self.last_event_id.clone(), event: event.unwrap_or_else(|| "message".to_string()), data, retry })
+    }
+}
+
+/// events of `text/event-stream` response, and connection is re-established with `Last-Event-ID` after closed
+pub struct EventStream {
+    receiver: mpsc::Receiver<Result<Event>>,
+}
+
+impl EventStream {
+    /// next event, and `None` after server stops reconnection with 204 status
+    pub async fn next_event(&mut self) -> Option<Result<Event>> {
+        self.receiver.recv().await
+    }
+}
+
+impl Stream for EventStream {
+    type Item = Result<Event>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// send request for event stream, and errors of first connection are returned directly
+pub async fn connect(location: &RequestLocation, request_builder: RequestBuilder) -> Result<EventStream> {
+    let retry_builder = request_builder.try_clone();
+    let response = open(location, request_builder).await?;
+    let reconnect = response.is_some();
+    let (sender, receiver) = mpsc::channel(16);
+    let location = location.clone();
+    tokio::spawn(async move {
+        let mut parser = EventParser::default();
+        let mut retry = DEFAULT_RETRY;
+        let mut response = response;
+        if !reconnect {
+            return;
+        }
+        loop {
+            if let Some(response) = response.take() {
+                let mut body = response.bytes_stream();
+                while let Some(chunk) = tokio::select! {
+                    chunk = body.next() => chunk,
+                    _ = sender.closed() => return,
+                } {
+                    let Ok(chunk) = chunk else { break };
+                    for event in parser.feed(&chunk) {
+                        if let Some(milliseconds) = event.retry {
+                            retry = Duration::from_millis(milliseconds);
+                        }
+                        if sender.send(Ok(event)).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            // reconnect after delay, and body-less request can't be cloned for reconnection
+            let Some(request_builder) = retry_builder.as_ref().and_then(|request_builder| request_builder.try_clone()) else { return };
+            tokio::select! {
+                _ = tokio::time::sleep(retry) => {}
+                _ = sender.closed() => return,
+            }
+            let request_builder = match parser.last_event_id() {
+                Some(last_event_id) => request_builder.header("Last-Event-ID", last_event_id),
+                None => request_builder,
+            };
+            response = match open(&location, request_builder).await {
+                Ok(Some(response)) => Some(response),
+                // server stops reconnection with 204 status
+                Ok(None) => return,
+                Err(Error::Transport { .. }) => None,
+                Err(error) => {
+                    let _ = sender.send(Err(error)).await;
+                    return;
+                }
+            };
+        }
+    });
+    Ok(EventStream { receiver })
+}
+
+/// open event stream, and `None` for 204 status to stop reconnection
+async fn open(location: &RequestLocation, request_builder: RequestBuilder) -> Result<Option<reqwest::Response>> {
+    let response = request_builder.header(ACCEPT, "text/event-stream").send().await.map_err(|error| Error::Transport { location: location.clone(), error })?;
+    let status = response.status();
+    if status == StatusCode::NO_CONTENT {
+        return Ok(None);
+    }
+    let content_type = response.headers().get(CONTENT_TYPE).and_then(|value| value.to_str().ok()).unwrap_or_default().to_string();
+    if !status.is_success() || !content_type.starts_with("text/event-stream") {
+        let body = response.text().await.unwrap_or_default();
+        return Err(Error::Status { location: location.clone(), status: status.as_u16(), body });
+    }
+    Ok(Some(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const LOCATION: RequestLocation = RequestLocation::new("progress", "index.http", 3);
+
+    /// mock server: two events with retry, then one more event after reconnected, and 204 to stop
+    async fn start_event_server() -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            for body in ["retry: 10\nid: 1\ndata: started\n\n: keep alive\nevent: progress\nid: 2\ndata: 50%\ndata: half\n\n", "data: done\n\n", ""] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0u8; 4096];
+                let size = stream.read(&mut buffer).await.unwrap();
+                recorded.lock().unwrap().push(String::from_utf8_lossy(&buffer[..size]).to_lowercase());
+                let response = if body.is_empty() {
+                    "HTTP/1.1 204 No Content\r\nconnection: close\r\n\r\n".to_string()
+                } else {
+                    format!("HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", body.len(), body)
+                };
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (format!("http://{}/events", address), requests)
+    }
+
+    #[test]
+    fn test_event_parser() {
+        let mut parser = EventParser::default();
+        assert!(parser.feed(b"event: update\r\ndata: {\"id\":").is_empty());
+        let events = parser.feed(b" 1}\r\n\r\ndata\n\n");
+        assert_eq!(events[0], Event { id: None, event: "update".to_string(), data: r#"{"id": 1}"#.to_string(), retry: None });
+        assert_eq!(events[1].data, "");
+        assert_eq!(events[1].event, "message");
+    }
+
+    #[tokio::test]
+    async fn test_connect() {
+        let (url, requests) = start_event_server().await;
+        let mut stream = connect(&LOCATION, reqwest::Client::new().get(&url)).await.unwrap();
+        let event = stream.next_event().await.unwrap().unwrap();
+        assert_eq!((event.id.as_deref(), event.data.as_str(), event.retry), (Some("1"), "started", Some(10)));
+        let event = stream.next_event().await.unwrap().unwrap();
+        assert_eq!((event.id.as_deref(), event.event.as_str(), event.data.as_str()), (Some("2"), "progress", "50%\nhalf"));
+        let event = stream.next().await.unwrap().unwrap();
+        assert_eq!((event.id.as_deref(), event.data.as_str()), (Some("2"), "done"));
+        assert!(stream.next_event().await.is_none());
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].contains("accept: text/event-stream"));
+        assert!(requests[1].contains("last-event-id: 2"));
+    }
+}
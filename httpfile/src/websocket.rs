@@ -0,0 +1,193 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use futures_util::Stream;
+use reqwest::header::HeaderMap;
+use tokio::sync::mpsc;
+#[cfg(feature = "websocket")]
+use futures_util::{SinkExt, StreamExt};
+#[cfg(feature = "websocket")]
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+#[cfg(feature = "websocket")]
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+#[cfg(feature = "websocket")]
+use tokio_tungstenite::tungstenite::Message;
+use crate::{Error, RequestLocation, Result};
+
+/// outgoing message of `WEBSOCKET` request, and it is sent after the count of server messages received
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutgoingMessage {
+    pub wait_for_server: usize,
+    pub text: String,
+}
+
+impl OutgoingMessage {
+    pub fn new(wait_for_server: usize, text: impl Into<String>) -> OutgoingMessage {
+        OutgoingMessage { wait_for_server, text: text.into() }
+    }
+}
+
+/// split body of `WEBSOCKET` request on `===` lines, and each `=== wait-for-server` line waits for one more server message
+pub fn parse_messages(body: &str) -> Vec<OutgoingMessage> {
+    let mut messages = Vec::new();
+    let mut wait_for_server = 0;
+    let mut lines: Vec<&str> = Vec::new();
+    for line in body.lines() {
+        if let Some(separator) = line.trim().strip_prefix("===") {
+            let text = lines.join("\n").trim().to_string();
+            if !text.is_empty() {
+                messages.push(OutgoingMessage::new(wait_for_server, text));
+                wait_for_server = 0;
+            }
+            lines.clear();
+            if separator.trim() == "wait-for-server" {
+                wait_for_server += 1;
+            }
+        } else {
+            lines.push(line);
+        }
+    }
+    let text = lines.join("\n").trim().to_string();
+    if !text.is_empty() {
+        messages.push(OutgoingMessage::new(wait_for_server, text));
+    }
+    messages
+}
+
+/// incoming text messages of WebSocket connection, and binary messages are decoded as UTF-8 lossily
+pub struct MessageStream {
+    receiver: mpsc::Receiver<Result<String>>,
+}
+
+impl MessageStream {
+    /// next incoming message, and `None` after connection closed
+    pub async fn next_message(&mut self) -> Option<Result<String>> {
+        self.receiver.recv().await
+    }
+}
+
+impl Stream for MessageStream {
+    type Item = Result<String>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// connect to WebSocket server with headers, and outgoing messages are sent in background; connection is closed after stream dropped
+#[cfg(feature = "websocket")]
+pub async fn connect(location: &RequestLocation, url: &str, headers: HeaderMap, messages: Vec<OutgoingMessage>) -> Result<MessageStream> {
+    let location = location.clone();
+    let websocket_error = move |message: String| Error::WebSocket { location: location.clone(), message };
+    let mut request = url.into_client_request().map_err(|e| websocket_error(e.to_string()))?;
+    for (name, value) in headers.iter() {
+        let name = HeaderName::from_bytes(name.as_str().as_bytes()).map_err(|e| websocket_error(e.to_string()))?;
+        let value = HeaderValue::from_bytes(value.as_bytes()).map_err(|e| websocket_error(e.to_string()))?;
+        request.headers_mut().insert(name, value);
+    }
+    let (websocket, _) = tokio_tungstenite::connect_async(request).await.map_err(|e| websocket_error(e.to_string()))?;
+    let (mut sink, mut stream) = websocket.split();
+    let (sender, receiver) = mpsc::channel(16);
+    tokio::spawn(async move {
+        let mut messages = messages.into_iter().peekable();
+        let mut received = 0;
+        loop {
+            // send messages whose server messages have been received
+            while let Some(message) = messages.next_if(|message| message.wait_for_server <= received) {
+                received -= message.wait_for_server;
+                if let Err(e) = sink.send(Message::text(message.text)).await {
+                    let _ = sender.send(Err(websocket_error(e.to_string()))).await;
+                    return;
+                }
+            }
+            let incoming = tokio::select! {
+                incoming = stream.next() => incoming,
+                _ = sender.closed() => {
+                    let _ = sink.close().await;
+                    return;
+                }
+            };
+            let text = match incoming {
+                Some(Ok(Message::Text(text))) => text,
+                Some(Ok(Message::Binary(bytes))) => String::from_utf8_lossy(&bytes).to_string(),
+                Some(Ok(Message::Close(_))) | None => return,
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => {
+                    let _ = sender.send(Err(websocket_error(e.to_string()))).await;
+                    return;
+                }
+            };
+            received += 1;
+            if sender.send(Ok(text)).await.is_err() {
+                let _ = sink.close().await;
+                return;
+            }
+        }
+    });
+    Ok(MessageStream { receiver })
+}
+
+/// connect to WebSocket server, and `websocket` feature of httpfile is required
+#[cfg(not(feature = "websocket"))]
+pub async fn connect(location: &RequestLocation, _url: &str, _headers: HeaderMap, _messages: Vec<OutgoingMessage>) -> Result<MessageStream> {
+    Err(Error::WebSocket { location: location.clone(), message: "`WEBSOCKET` request requires `websocket` feature of httpfile".to_owned() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "websocket")]
+    use futures_util::StreamExt;
+    #[cfg(feature = "websocket")]
+    use tokio::net::TcpListener;
+
+    const LOCATION: RequestLocation = RequestLocation::new("chat", "index.http", 3);
+
+    /// mock server: greets after connected, and echoes messages with prefix
+    #[cfg(feature = "websocket")]
+    async fn start_echo_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut websocket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            websocket.send(Message::text("welcome")).await.unwrap();
+            while let Some(Ok(message)) = websocket.next().await {
+                if let Message::Text(text) = message {
+                    websocket.send(Message::text(format!("echo: {}", text))).await.unwrap();
+                }
+            }
+        });
+        format!("ws://{}/ws", address)
+    }
+
+    #[test]
+    fn test_parse_messages() {
+        let body = r#"===
+{"message": "first"}
+=== wait-for-server
+=== wait-for-server
+{"message": "second"}"#;
+        let messages = parse_messages(body);
+        assert_eq!(messages, vec![OutgoingMessage::new(0, r#"{"message": "first"}"#), OutgoingMessage::new(2, r#"{"message": "second"}"#)]);
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn test_connect() {
+        let url = start_echo_server().await;
+        let messages = vec![OutgoingMessage::new(1, "first"), OutgoingMessage::new(1, "second")];
+        let mut stream = connect(&LOCATION, &url, HeaderMap::new(), messages).await.unwrap();
+        assert_eq!(stream.next_message().await.unwrap().unwrap(), "welcome");
+        assert_eq!(stream.next_message().await.unwrap().unwrap(), "echo: first");
+        assert_eq!(stream.next().await.unwrap().unwrap(), "echo: second");
+        let result = connect(&LOCATION, "ws://127.0.0.1:1/ws", HeaderMap::new(), Vec::new()).await;
+        assert!(matches!(result, Err(Error::WebSocket { .. })));
+    }
+
+    #[cfg(not(feature = "websocket"))]
+    #[tokio::test]
+    async fn test_websocket_feature_required() {
+        let error = connect(&LOCATION, "ws://127.0.0.1:1/ws", HeaderMap::new(), Vec::new()).await.err().unwrap();
+        assert!(error.to_string().contains("`WEBSOCKET` request requires `websocket` feature of httpfile"));
+    }
+}